@@ -0,0 +1,531 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discover::find_exercise_files;
+use crate::eval::{ExerciseResult, Grader};
+use crate::generic::{Language, generic_exercises};
+use crate::msg;
+use crate::report::Reporter;
+use crate::selection::Selection;
+
+/// rustlings 课程根目录下的 info.toml，只解析用到的字段
+#[derive(Deserialize, Debug)]
+pub struct InfoFile {
+    #[serde(default)]
+    pub exercises: Vec<InfoExercise>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InfoExercise {
+    pub name: String,
+    /// 练习提示，空行分隔的段落依次作为逐级提示
+    #[serde(default)]
+    pub hint: String,
+    /// 旧版 rustlings 的评测方式：test、compile 或 clippy
+    pub mode: Option<String>,
+    /// 新版 rustlings 中练习是否带有测试，默认为 true
+    pub test: Option<bool>,
+    /// 新版 rustlings 中练习是否要求 clippy 没有警告
+    #[serde(default)]
+    pub strict_clippy: bool,
+}
+
+/// rustlings 练习的评测方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExerciseMode {
+    /// 运行练习中的测试；为 false 时只运行练习的 main 函数
+    pub test: bool,
+    /// 先用 `clippy -D warnings` 检查
+    pub clippy: bool,
+}
+
+impl InfoExercise {
+    /// info.toml 中的设置对应的评测方式
+    ///
+    /// 旧版的 clippy 模式只要求 clippy 没有警告，这类练习不带测试，所以和 compile 模式一样
+    /// 构建后运行 main 函数，而不是运行测试。
+    pub fn exercise_mode(&self) -> ExerciseMode {
+        match self.mode.as_deref() {
+            Some("compile") => ExerciseMode {
+                test: false,
+                clippy: false,
+            },
+            Some("clippy") => ExerciseMode {
+                test: false,
+                clippy: true,
+            },
+            Some(_) => ExerciseMode {
+                test: true,
+                clippy: false,
+            },
+            None => ExerciseMode {
+                test: self.test.unwrap_or(true),
+                clippy: self.strict_clippy,
+            },
+        }
+    }
+}
+
+impl InfoFile {
+    /// 读取课程的 info.toml，不存在时返回 None
+    pub fn load(course_path: &Path) -> Result<Option<Self>> {
+        let info_path = course_path.join("info.toml");
+        if !info_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&info_path)
+            .with_context(|| msg!("读取 {} 失败", info_path.display()))?;
+        let info =
+            toml::from_str(&content).with_context(|| msg!("解析 {} 失败", info_path.display()))?;
+        Ok(Some(info))
+    }
+}
+
+/// 按课程顺序排列练习文件：有 info.toml 时按其中的顺序，其余按路径排序
+pub fn sort_by_course_order(course_path: &Path, files: &mut [PathBuf]) -> Result<()> {
+    let order: Vec<String> = InfoFile::load(course_path)?
+        .map(|info| info.exercises.into_iter().map(|e| e.name).collect())
+        .unwrap_or_default();
+
+    files.sort_by_cached_key(|path| {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let index = order.iter().position(|name| *name == stem);
+        (index.unwrap_or(usize::MAX), path.clone())
+    });
+    Ok(())
+}
+
+/// 课程根目录下的 Cargo.toml，只解析用到的字段
+#[derive(Deserialize, Default)]
+pub struct CargoManifest {
+    #[serde(default)]
+    package: ManifestPackage,
+    /// 新版 rustlings 把每个练习声明为一个 `[[bin]]`
+    #[serde(default)]
+    pub bin: Vec<CargoBin>,
+}
+
+#[derive(Deserialize, Default)]
+struct ManifestPackage {
+    edition: Option<String>,
+    /// learning-lm-rs 的 `[package.metadata.xtask]`
+    #[serde(default)]
+    metadata: ManifestMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CargoBin {
+    pub name: String,
+    /// 相对课程目录
+    pub path: PathBuf,
+}
+
+impl CargoManifest {
+    /// 读取课程的 Cargo.toml，不存在时返回 None
+    pub fn load(course_path: &Path) -> Result<Option<Self>> {
+        let manifest_path = course_path.join("Cargo.toml");
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&manifest_path)
+            .with_context(|| msg!("读取 {} 失败", manifest_path.display()))?;
+        let manifest = toml::from_str(&content)
+            .with_context(|| msg!("解析 {} 失败", manifest_path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    pub fn edition(&self) -> Option<&str> {
+        self.package.edition.as_deref()
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct ManifestMetadata {
+    #[serde(default)]
+    xtask: XtaskMetadata,
+}
+
+#[derive(Deserialize, Default)]
+struct XtaskMetadata {
+    /// 需要评测的源文件，相对课程目录
+    #[serde(default)]
+    exercises: Vec<PathBuf>,
+}
+
+/// 课程清单没有列出练习时使用的默认练习
+const LEARNING_LM_EXERCISES: [&str; 2] = ["src/model.rs", "src/operators.rs"];
+
+/// learning-lm-rs 需要评测的源文件（相对课程目录），来自 Cargo.toml 的
+/// `[package.metadata.xtask] exercises`，未配置时为 model.rs 和 operators.rs
+pub fn learning_lm_exercises(course_path: &Path) -> Result<Vec<PathBuf>> {
+    let manifest = CargoManifest::load(course_path)?
+        .with_context(|| msg!("找不到 {}", course_path.join("Cargo.toml").display()))?;
+
+    let exercises = manifest.package.metadata.xtask.exercises;
+    if exercises.is_empty() {
+        return Ok(LEARNING_LM_EXERCISES.iter().map(PathBuf::from).collect());
+    }
+    Ok(exercises)
+}
+
+/// learning-lm-rs 练习的名称：相对 src 的路径，例如 `operators.rs`、`tensor/mod.rs`
+pub fn learning_lm_exercise_name(relative: &Path) -> String {
+    relative
+        .strip_prefix("src")
+        .unwrap_or(relative)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// 源文件对应的模块路径，例如 `src/operators.rs` 为 `operators`，`src/a/mod.rs` 为 `a`，
+/// crate 根 `src/lib.rs` 和 `src/main.rs` 为空
+pub fn module_path(relative: &Path) -> String {
+    let relative = relative.strip_prefix("src").unwrap_or(relative);
+    let mut parts: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.last().is_some_and(|last| last == "mod") {
+        parts.pop();
+    }
+    if parts.len() == 1 && (parts[0] == "lib" || parts[0] == "main") {
+        parts.pop();
+    }
+    parts.join("::")
+}
+
+/// 测试（如 `operators::tests::test_silu`）所在的源文件：模块路径是测试名前缀的文件中最长的一个，
+/// 这样 `src/a/mod.rs` 不会算上 `src/a/b.rs` 中的测试
+pub fn test_owner<'a>(test: &str, files: &'a [PathBuf]) -> Option<&'a Path> {
+    files
+        .iter()
+        .map(|file| (file, module_path(file)))
+        .filter(|(_, module)| module.is_empty() || test.starts_with(&format!("{}::", module)))
+        .max_by_key(|(_, module)| module.len())
+        .map(|(file, _)| file.as_path())
+}
+
+/// learning-cxx 的一个练习目录，例如 `exercises/05_xxx` 对应 exercise05
+pub struct CxxExercise {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+/// 按编号顺序列出 learning-cxx 的练习目录
+pub fn cxx_exercises(course_path: &Path) -> Result<Vec<CxxExercise>> {
+    let exercises_dir = course_path.join("exercises");
+    if !exercises_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut exercises = Vec::new();
+    for entry in fs::read_dir(&exercises_dir)
+        .with_context(|| msg!("无法读取目录: {}", exercises_dir.display()))?
+    {
+        let entry = entry.context(msg!("读取目录项失败"))?;
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let number: String = dir_name.chars().take_while(char::is_ascii_digit).collect();
+        if number.is_empty() {
+            continue;
+        }
+        exercises.push(CxxExercise {
+            name: format!("exercise{}", number),
+            dir: entry.path(),
+        });
+    }
+    exercises.sort_by(|a, b| a.dir.cmp(&b.dir));
+    Ok(exercises)
+}
+
+/// 课程的评测方式：按课程名称选择，其他课程按目录内容识别语言，识别不出时按 rustlings 方式评测
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CourseKind {
+    LearningLm,
+    LearningCxx,
+    Generic(Language),
+    Rustlings,
+}
+
+impl CourseKind {
+    pub fn of(course: &str, course_path: &Path) -> Self {
+        match course {
+            "learning-lm-rs" => CourseKind::LearningLm,
+            "learning-cxx" => CourseKind::LearningCxx,
+            _ => match Language::detect(course_path) {
+                Some(language) => CourseKind::Generic(language),
+                None => CourseKind::Rustlings,
+            },
+        }
+    }
+}
+
+/// 课程中的评测单元，按课程顺序排列
+pub enum Unit {
+    /// rustlings 等课程的单个练习文件
+    Rust(PathBuf),
+    /// learning-cxx 的单个练习目录
+    Cxx(CxxExercise),
+    /// learning-lm-rs 需要补全的源文件，测试只能整体运行
+    LearningLm { course_path: PathBuf, file: PathBuf },
+    /// C、Python、Go 课程的一个练习
+    Generic {
+        language: Language,
+        name: String,
+        path: PathBuf,
+    },
+}
+
+impl Unit {
+    /// 与 eval_result.json 中的练习名称一致
+    pub fn name(&self) -> String {
+        match self {
+            Unit::Rust(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Unit::Cxx(exercise) => exercise.name.clone(),
+            Unit::LearningLm { course_path, file } => {
+                learning_lm_exercise_name(file.strip_prefix(course_path).unwrap_or(file))
+            }
+            Unit::Generic { name, .. } => name.clone(),
+        }
+    }
+
+    /// 查找提示文件时使用的名称：练习文件名去掉扩展名、learning-lm-rs 的模块路径，或 learning-cxx 的目录名
+    pub fn hint_key(&self) -> String {
+        match self {
+            Unit::Rust(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Unit::LearningLm { course_path, file } => {
+                let module = module_path(file.strip_prefix(course_path).unwrap_or(file));
+                if module.is_empty() {
+                    // crate 根没有模块路径，使用文件名
+                    file.file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                } else {
+                    module.replace("::", "/")
+                }
+            }
+            Unit::Cxx(exercise) => exercise
+                .dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Unit::Generic { path, .. } => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 练习所在的文件或目录，提示学员下一步去哪里
+    pub fn location(&self) -> &Path {
+        match self {
+            Unit::Rust(path) => path,
+            Unit::Cxx(exercise) => &exercise.dir,
+            Unit::LearningLm { file, .. } => file,
+            Unit::Generic { path, .. } => path,
+        }
+    }
+
+    /// 练习相对课程目录的路径，课程根目录为 `.`，用于只评测这个练习
+    pub fn relative(&self, course_path: &Path) -> PathBuf {
+        let relative = self
+            .location()
+            .strip_prefix(course_path)
+            .unwrap_or(self.location());
+        if relative.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            relative.to_path_buf()
+        }
+    }
+
+    /// 修改 `changed` 后是否需要重新评测这个单元
+    pub fn contains(&self, changed: &Path) -> bool {
+        match self {
+            Unit::Rust(path) => changed == path,
+            Unit::Cxx(exercise) => changed.starts_with(&exercise.dir),
+            Unit::LearningLm { file, .. } => changed == file,
+            Unit::Generic {
+                language: Language::C,
+                path,
+                ..
+            } => changed.starts_with(path),
+            // 子目录是另一个包
+            Unit::Generic {
+                language: Language::Go,
+                path,
+                ..
+            } => changed.parent() == Some(path.as_path()),
+            // 测试文件之外，也包括按命名约定对应的源文件，如 test_foo.py 对应 foo.py
+            Unit::Generic {
+                language: Language::Python,
+                path,
+                ..
+            } => {
+                let stem = |path: &Path| {
+                    path.file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                };
+                let tested = stem(path);
+                let tested = tested
+                    .strip_prefix("test_")
+                    .or_else(|| tested.strip_suffix("_test"))
+                    .unwrap_or(&tested);
+                changed == path
+                    || (changed.extension().is_some_and(|ext| ext == "py")
+                        && stem(changed) == tested)
+            }
+        }
+    }
+
+    /// 评测这个单元，与 eval 使用相同的评测器和选项，包括测试代码检查、未完成检查和标准测试
+    pub fn grade(
+        &self,
+        grader: &Grader,
+        course: &str,
+        course_path: &Path,
+        reporter: &dyn Reporter,
+    ) -> Result<ExerciseResult> {
+        let selection = Selection::path(&self.relative(course_path));
+        grader
+            .grade_course(
+                course,
+                course_path,
+                &selection.for_course(course, course_path)?,
+                reporter,
+            )?
+            .into_iter()
+            .next()
+            .with_context(|| msg!("找不到练习: {}", self.name()))
+    }
+}
+
+/// 按课程顺序列出评测单元，与 eval 的课程分派保持一致
+pub fn course_units(course: &str, course_path: &Path) -> Result<Vec<Unit>> {
+    let units = match CourseKind::of(course, course_path) {
+        CourseKind::LearningLm => learning_lm_exercises(course_path)?
+            .into_iter()
+            .map(|file| Unit::LearningLm {
+                course_path: course_path.to_path_buf(),
+                file: course_path.join(file),
+            })
+            .collect(),
+        CourseKind::LearningCxx => cxx_exercises(course_path)?
+            .into_iter()
+            .map(Unit::Cxx)
+            .collect(),
+        CourseKind::Generic(language) => generic_exercises(language, course_path)?
+            .into_iter()
+            .map(|exercise| Unit::Generic {
+                language,
+                name: exercise.name,
+                path: exercise.path,
+            })
+            .collect(),
+        CourseKind::Rustlings => {
+            let mut files = find_exercise_files(course_path)?;
+            sort_by_course_order(course_path, &mut files)?;
+            files.into_iter().map(Unit::Rust).collect()
+        }
+    };
+    Ok(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_settings_map_to_exercise_modes() {
+        let mode = |test: bool, clippy: bool| ExerciseMode { test, clippy };
+        let cases = [
+            (Some("compile"), None, false, mode(false, false)),
+            (Some("clippy"), None, false, mode(false, true)),
+            (Some("test"), None, false, mode(true, false)),
+            // 旧版的 mode 优先于新版的设置
+            (Some("compile"), Some(true), true, mode(false, false)),
+            (None, None, false, mode(true, false)),
+            (None, Some(true), false, mode(true, false)),
+            (None, Some(false), false, mode(false, false)),
+            (None, None, true, mode(true, true)),
+            (None, Some(false), true, mode(false, true)),
+        ];
+        for (info_mode, test, strict_clippy, expected) in cases {
+            let exercise = InfoExercise {
+                name: "exercise".to_string(),
+                hint: String::new(),
+                mode: info_mode.map(str::to_string),
+                test,
+                strict_clippy,
+            };
+            assert_eq!(
+                exercise.exercise_mode(),
+                expected,
+                "mode = {:?}, test = {:?}, strict_clippy = {}",
+                info_mode,
+                test,
+                strict_clippy
+            );
+        }
+    }
+
+    #[test]
+    fn module_path_of_source_files() {
+        assert_eq!(module_path(Path::new("src/operators.rs")), "operators");
+        assert_eq!(module_path(Path::new("src/tensor/mod.rs")), "tensor");
+        assert_eq!(
+            module_path(Path::new("src/model/attention.rs")),
+            "model::attention"
+        );
+        assert_eq!(module_path(Path::new("src/lib.rs")), "");
+        assert_eq!(module_path(Path::new("src/model/lib.rs")), "model::lib");
+    }
+
+    #[test]
+    fn tests_belong_to_the_innermost_module() {
+        let files = [
+            PathBuf::from("src/model/mod.rs"),
+            PathBuf::from("src/model/attention.rs"),
+            PathBuf::from("src/operators.rs"),
+        ];
+        let owner = |test| test_owner(test, &files).map(Path::to_path_buf);
+        assert_eq!(owner("model::tests::load"), Some(files[0].clone()));
+        assert_eq!(
+            owner("model::attention::tests::mask"),
+            Some(files[1].clone())
+        );
+        assert_eq!(owner("operators::tests::test_silu"), Some(files[2].clone()));
+        // 前缀按整段模块名匹配
+        assert_eq!(owner("operators_ext::tests::rope"), None);
+        assert_eq!(owner("tests::smoke"), None);
+    }
+
+    #[test]
+    fn crate_root_owns_the_remaining_tests() {
+        let files = [
+            PathBuf::from("src/lib.rs"),
+            PathBuf::from("src/operators.rs"),
+        ];
+        assert_eq!(test_owner("tests::smoke", &files), Some(files[0].as_path()));
+        assert_eq!(
+            test_owner("operators::tests::test_silu", &files),
+            Some(files[1].as_path())
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::msg;
+use tracing::debug_span;
+
+/// 失败原因中最多列出的诊断条数
+const MAX_REASON_DIAGNOSTICS: usize = 3;
+
+/// 一条编译错误，来自 rustc 的 `--error-format=json` 或 cargo 的 `--message-format=json`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    /// 主要位置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// 附带的 note 和 help 信息
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// 编译器给出的修改建议
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Span {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// rustc JSON 诊断格式中用到的字段
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    code: Option<RustcCode>,
+    level: String,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+    #[serde(default)]
+    children: Vec<RustcDiagnostic>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+}
+
+/// cargo 的 JSON 消息，编译器诊断包在 `message` 字段里
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RustcDiagnostic>,
+}
+
+impl RustcSpan {
+    fn to_span(&self) -> Span {
+        Span {
+            file: self.file_name.clone(),
+            line: self.line_start,
+            column: self.column_start,
+            label: self.label.clone(),
+        }
+    }
+}
+
+impl Diagnostic {
+    fn from_rustc(diagnostic: &RustcDiagnostic) -> Self {
+        let mut notes = Vec::new();
+        let mut suggestions = Vec::new();
+        for child in &diagnostic.children {
+            let replacements: Vec<&RustcSpan> = child
+                .spans
+                .iter()
+                .filter(|span| span.suggested_replacement.is_some())
+                .collect();
+            if replacements.is_empty() {
+                notes.push(format!("{}: {}", child.level, child.message));
+            }
+            for span in replacements {
+                suggestions.push(Suggestion {
+                    message: child.message.clone(),
+                    span: span.to_span(),
+                    replacement: span.suggested_replacement.clone().unwrap_or_default(),
+                });
+            }
+        }
+
+        Diagnostic {
+            level: diagnostic.level.clone(),
+            code: diagnostic.code.as_ref().map(|code| code.code.clone()),
+            message: diagnostic.message.clone(),
+            span: diagnostic
+                .spans
+                .iter()
+                .find(|span| span.is_primary)
+                .map(RustcSpan::to_span),
+            notes,
+            suggestions,
+        }
+    }
+
+    /// 单行标题，例如 `error[E0308]: mismatched types`
+    pub fn headline(&self) -> String {
+        match &self.code {
+            Some(code) => format!("{}[{}]: {}", self.level, code, self.message),
+            None => format!("{}: {}", self.level, self.message),
+        }
+    }
+
+    /// 位置、提示和修改建议，每项一行
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(span) = &self.span {
+            let mut location = format!("--> {}:{}:{}", span.file, span.line, span.column);
+            if let Some(label) = span.label.as_ref().filter(|label| !label.is_empty()) {
+                location.push_str(&format!(" ({})", label));
+            }
+            lines.push(location);
+        }
+        lines.extend(self.notes.iter().cloned());
+        for suggestion in &self.suggestions {
+            lines.push(msg!(
+                "建议: {} `{}` ({}:{})",
+                suggestion.message,
+                suggestion.replacement,
+                suggestion.span.line,
+                suggestion.span.column
+            ));
+        }
+        lines
+    }
+
+    /// 纯文本形式，用作失败原因
+    pub fn render(&self) -> String {
+        let mut lines = vec![self.headline()];
+        lines.extend(self.details().into_iter().map(|line| format!("  {}", line)));
+        lines.join("\n")
+    }
+}
+
+/// 编译输出的解析结果
+pub struct CompilerOutput {
+    /// 错误级别的诊断
+    pub diagnostics: Vec<Diagnostic>,
+    /// 编译器自带的完整文本输出，用于 `--verbose`
+    pub rendered: String,
+}
+
+/// 解析 rustc 或 cargo 输出的 JSON 行，忽略无法解析的行和不带位置的汇总信息
+pub fn parse_compiler_output(output: &[u8]) -> CompilerOutput {
+    let _span = debug_span!("parse").entered();
+    let mut diagnostics = Vec::new();
+    let mut rendered = String::new();
+    for line in String::from_utf8_lossy(output).lines() {
+        let diagnostic = match serde_json::from_str::<RustcDiagnostic>(line) {
+            Ok(diagnostic) => diagnostic,
+            Err(_) => match serde_json::from_str::<CargoMessage>(line) {
+                Ok(CargoMessage {
+                    reason,
+                    message: Some(message),
+                }) if reason == "compiler-message" => message,
+                _ => continue,
+            },
+        };
+
+        if let Some(text) = &diagnostic.rendered {
+            rendered.push_str(text);
+        }
+        // "aborting due to ..." 之类的汇总信息没有位置和错误码
+        let is_summary = diagnostic.spans.is_empty() && diagnostic.code.is_none();
+        if diagnostic.level.starts_with("error") && !is_summary {
+            diagnostics.push(Diagnostic::from_rustc(&diagnostic));
+        }
+    }
+    CompilerOutput {
+        diagnostics,
+        rendered,
+    }
+}
+
+/// 去掉终端颜色控制序列，xmake 和 gcc 的输出常常带有颜色
+pub fn strip_ansi(text: &str) -> String {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]").unwrap();
+    ansi.replace_all(text, "").to_string()
+}
+
+/// 解析 gcc/clang 的 `file:line:column: error: message` 格式，紧随其后的 note 作为附带说明
+pub fn parse_gcc_output(output: &str) -> Vec<Diagnostic> {
+    let _span = debug_span!("parse").entered();
+    let line_re = Regex::new(r"^(.+?):(\d+):(\d+): (fatal error|error|note): (.*)$").unwrap();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in strip_ansi(output).lines() {
+        let Some(caps) = line_re.captures(line.trim_start_matches("error: ")) else {
+            continue;
+        };
+        let level = &caps[4];
+        let message = caps[5].to_string();
+        if level == "note" {
+            if let Some(last) = diagnostics.last_mut() {
+                last.notes.push(format!("note: {}", message));
+            }
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            level: "error".to_string(),
+            code: None,
+            message,
+            span: Some(Span {
+                file: caps[1].to_string(),
+                line: caps[2].parse().unwrap_or(0),
+                column: caps[3].parse().unwrap_or(0),
+                label: None,
+            }),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        });
+    }
+    diagnostics
+}
+
+/// 把前几条诊断拼成失败原因
+pub fn diagnostics_reason(diagnostics: &[Diagnostic]) -> Option<String> {
+    if diagnostics.is_empty() {
+        return None;
+    }
+    let mut reason = diagnostics
+        .iter()
+        .take(MAX_REASON_DIAGNOSTICS)
+        .map(Diagnostic::render)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if diagnostics.len() > MAX_REASON_DIAGNOSTICS {
+        reason.push('\n');
+        reason.push_str(&msg!(
+            "... 还有 {} 个错误",
+            diagnostics.len() - MAX_REASON_DIAGNOSTICS
+        ));
+    }
+    Some(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `rustc --edition 2021 --error-format=json` 编译 rustlings 的 variables3 得到的输出
+    const RUSTC: &str = include_str!("../tests/fixtures/rustc.json");
+    /// `cargo build --message-format=json` 的输出
+    const CARGO: &str = include_str!("../tests/fixtures/cargo.json");
+    /// gcc 12 带 `-fdiagnostics-color=always` 的输出
+    const GCC: &str = include_str!("../tests/fixtures/gcc.txt");
+
+    #[test]
+    fn rustc_json_keeps_errors_with_suggestions() {
+        let output = parse_compiler_output(RUSTC.as_bytes());
+        // 警告、"aborting due to ..." 汇总和 failure-note 都不算
+        assert_eq!(output.diagnostics.len(), 1);
+
+        let diagnostic = &output.diagnostics[0];
+        assert_eq!(
+            diagnostic.headline(),
+            "error[E0384]: cannot assign twice to immutable variable `x`"
+        );
+        let span = diagnostic.span.as_ref().unwrap();
+        assert_eq!(
+            (span.file.as_str(), span.line, span.column),
+            ("variables3.rs", 5, 5)
+        );
+        assert_eq!(
+            span.label.as_deref(),
+            Some("cannot assign twice to immutable variable")
+        );
+        assert!(diagnostic.notes.is_empty());
+        assert_eq!(diagnostic.suggestions.len(), 1);
+        let suggestion = &diagnostic.suggestions[0];
+        assert_eq!(suggestion.message, "consider making this binding mutable");
+        assert_eq!(suggestion.replacement, "mut ");
+        assert_eq!((suggestion.span.line, suggestion.span.column), (3, 9));
+
+        // 完整的文本输出包含警告和汇总
+        assert!(output.rendered.starts_with("error[E0384]"));
+        assert!(
+            output
+                .rendered
+                .contains("warning: unused variable: `unused`")
+        );
+        assert!(output.rendered.contains("aborting due to 1 previous error"));
+    }
+
+    #[test]
+    fn cargo_messages_unwrap_compiler_diagnostics() {
+        let output = parse_compiler_output(CARGO.as_bytes());
+        assert_eq!(output.diagnostics.len(), 1);
+        let diagnostic = &output.diagnostics[0];
+        assert_eq!(diagnostic.headline(), "error[E0308]: mismatched types");
+        assert_eq!(
+            diagnostic.render(),
+            "error[E0308]: mismatched types\n  --> src/main.rs:5:18 (expected `i32`, found `&str`)"
+        );
+        assert!(output.rendered.contains("expected due to this"));
+    }
+
+    #[test]
+    fn non_json_lines_are_ignored() {
+        let output = parse_compiler_output(b"   Compiling demo v0.1.0\nnot json\n");
+        assert!(output.diagnostics.is_empty());
+        assert!(output.rendered.is_empty());
+    }
+
+    #[test]
+    fn gcc_errors_with_colors_and_notes() {
+        let diagnostics = parse_gcc_output(GCC);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(
+            diagnostics[0].render(),
+            "error: 'undefined_value' undeclared (first use in this function)\n  \
+             --> main.c:3:13\n  \
+             note: each undeclared identifier is reported only once for each function it appears in"
+        );
+        assert_eq!(diagnostics[1].message, "expected ';' before 'return'");
+        let span = diagnostics[1].span.as_ref().unwrap();
+        assert_eq!(
+            (span.file.as_str(), span.line, span.column),
+            ("main.c", 4, 22)
+        );
+        assert!(diagnostics[1].notes.is_empty());
+    }
+
+    #[test]
+    fn gcc_fatal_error_and_build_tool_prefix() {
+        // xmake 会在编译器输出前加上 `error: `
+        let output = "error: main.cpp:1:10: fatal error: missing.h: No such file or directory\n";
+        let diagnostics = parse_gcc_output(output);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].level, "error");
+        assert_eq!(
+            diagnostics[0].message,
+            "missing.h: No such file or directory"
+        );
+        assert_eq!(diagnostics[0].span.as_ref().unwrap().file, "main.cpp");
+    }
+
+    #[test]
+    fn reason_lists_the_first_diagnostics() {
+        assert_eq!(diagnostics_reason(&[]), None);
+
+        let diagnostics = parse_compiler_output(RUSTC.repeat(4).as_bytes()).diagnostics;
+        assert_eq!(diagnostics.len(), 4);
+        let reason = diagnostics_reason(&diagnostics).unwrap();
+        assert_eq!(
+            reason.matches("error[E0384]").count(),
+            MAX_REASON_DIAGNOSTICS
+        );
+        assert!(reason.ends_with("\n... 还有 1 个错误"), "{}", reason);
+    }
+}
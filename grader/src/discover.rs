@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::course::learning_lm_exercises;
+use crate::gitmodules::{GitModules, Submodule};
+use crate::msg;
+use crate::rustlings::RustCourse;
+use tracing::{debug, warn};
+
+/// exercises 下的一个课程目录
+#[derive(Clone, Debug)]
+pub struct CourseDir {
+    pub name: String,
+    pub path: PathBuf,
+    /// 课程已登记为子模块但目录不存在或为空时，对应的子模块配置
+    pub uninitialized: Option<Submodule>,
+}
+
+/// 练习所在的 exercises 目录：`path` 本身以 exercises 结尾时就是它，否则为其下的 exercises
+pub fn exercises_dir(path: &Path) -> PathBuf {
+    if path.ends_with("exercises") {
+        path.to_path_buf()
+    } else {
+        path.join("exercises")
+    }
+}
+
+/// exercises 目录下指定名称的课程，目录不一定存在
+pub fn course_dir(exercises_dir: &Path, name: &str) -> Result<CourseDir> {
+    let gitmodules = load_gitmodules(exercises_dir)?;
+    Ok(new_course_dir(&gitmodules, exercises_dir.join(name), name))
+}
+
+/// exercises 目录下的所有课程，按目录的读取顺序
+pub fn course_dirs(exercises_dir: &Path) -> Result<Vec<CourseDir>> {
+    let gitmodules = load_gitmodules(exercises_dir)?;
+    let entries =
+        fs::read_dir(exercises_dir).context(msg!("无法读取目录: {}", exercises_dir.display()))?;
+    let mut courses = Vec::new();
+    for entry in entries {
+        let path = entry.context(msg!("读取目录项失败"))?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(msg!("未知课程"))
+            .to_string();
+        courses.push(new_course_dir(&gitmodules, path, &name));
+    }
+    Ok(courses)
+}
+
+/// 读取仓库的 .gitmodules，用于识别尚未检出的课程子模块
+fn load_gitmodules(exercises_dir: &Path) -> Result<GitModules> {
+    let workspace_dir = exercises_dir.parent().unwrap_or(exercises_dir);
+    GitModules::load(&workspace_dir.join(".gitmodules"))
+}
+
+fn new_course_dir(gitmodules: &GitModules, path: PathBuf, name: &str) -> CourseDir {
+    let is_empty = fs::read_dir(&path).map_or(true, |mut entries| entries.next().is_none());
+    let uninitialized = gitmodules
+        .find_by_path(&format!("exercises/{}", name))
+        .filter(|_| is_empty)
+        .cloned();
+    CourseDir {
+        name: name.to_string(),
+        path,
+        uninitialized,
+    }
+}
+
+/// 查找指定目录下的所有练习文件
+pub fn find_exercise_files(course_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut exercise_files = Vec::new();
+
+    if !course_path.exists() {
+        warn!(path = %course_path.display(), "找不到课程目录");
+        return Ok(Vec::new());
+    }
+
+    // 对于learning-lm-rs项目，只返回课程清单中列出的源文件
+    if course_path
+        .file_name()
+        .is_some_and(|name| name == "learning-lm-rs")
+        && course_path.join("Cargo.toml").exists()
+    {
+        debug!(path = %course_path.display(), "找到 learning-lm-rs 项目");
+        for relative in learning_lm_exercises(course_path)? {
+            let path = course_path.join(&relative);
+            if path.exists() {
+                debug!(path = %path.display(), "找到练习文件");
+                exercise_files.push(path);
+            } else {
+                warn!(path = %relative.display(), "找不到练习文件");
+            }
+        }
+
+        return Ok(exercise_files);
+    }
+
+    // 新版 rustlings 在 Cargo.toml 中用 [[bin]] 声明练习
+    if let Some(files) = RustCourse::load(course_path)?.exercise_files() {
+        return Ok(files);
+    }
+
+    // 对于rustlings项目，只查找exercises目录下的文件
+    if course_path
+        .file_name()
+        .is_some_and(|name| name == "rustlings")
+    {
+        let exercises_path = course_path.join("exercises");
+        if !exercises_path.exists() {
+            warn!("找不到 rustlings 的 exercises 目录");
+            return Ok(Vec::new());
+        }
+
+        for entry in walkdir::WalkDir::new(&exercises_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
+                let file_name = path.file_name().unwrap().to_string_lossy();
+                if !file_name.starts_with("test_") && !file_name.starts_with("helper_") {
+                    exercise_files.push(path.to_path_buf());
+                }
+            }
+        }
+    } else {
+        // 对于其他项目，遍历目录查找练习文件
+        for entry in walkdir::WalkDir::new(course_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if path.components().any(|c| c.as_os_str() == "target") {
+                continue;
+            }
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
+                let file_name = path.file_name().unwrap().to_string_lossy();
+                if !file_name.starts_with("test_") && !file_name.starts_with("helper_") {
+                    exercise_files.push(path.to_path_buf());
+                }
+            }
+        }
+    }
+
+    Ok(exercise_files)
+}
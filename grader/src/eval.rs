@@ -0,0 +1,926 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::course::{
+    CourseKind, CxxExercise, cxx_exercises, learning_lm_exercise_name, learning_lm_exercises,
+    module_path, sort_by_course_order, test_owner,
+};
+use crate::diagnostics::{Diagnostic, diagnostics_reason, parse_gcc_output, strip_ansi};
+use crate::discover::find_exercise_files;
+use crate::generic::eval_generic;
+use crate::i18n::Lang;
+use crate::libtest::{TestResult, TestStatus, run_libtest, tests_reason};
+use crate::logs::{ExerciseLog, LoggedOutput, RunLog, SHARED_LOG};
+use crate::msg;
+use crate::report::{Evaluator, Event, Reporter};
+use crate::rustlings::RustCourse;
+use crate::sandbox::{Sandbox, cargo_writable};
+use crate::selection::CourseSelection;
+use crate::tamper::{TamperCheck, TamperPolicy};
+use crate::unfinished::UnfinishedCheck;
+use tracing::{debug, debug_span, info_span, warn};
+
+/// learning-cxx 的评测方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CxxBackend {
+    /// 通过 xmake 构建并运行练习目标
+    #[default]
+    Xmake,
+    /// 不依赖 xmake，直接用 g++ 或 clang++ 编译练习
+    Direct,
+}
+
+/// C++ 练习的评测选项
+#[derive(Clone, Debug)]
+pub struct CxxOptions {
+    pub backend: CxxBackend,
+    /// direct 方式使用的 C++ 标准，额外的编译选项可以通过 CXXFLAGS 环境变量传入
+    pub std: String,
+}
+
+impl Default for CxxOptions {
+    fn default() -> Self {
+        CxxOptions {
+            backend: CxxBackend::Xmake,
+            std: "c++17".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExerciseResult {
+    pub name: String,
+    /// 练习所属的课程，不同课程中可能有同名练习；旧版评测结果中没有
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub course: String,
+    pub result: bool,
+    /// 未通过时的简短原因，供 next 命令展示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// 编译失败时的结构化诊断
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+    /// 练习中各个测试的结果
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<TestResult>,
+    /// 评测本身出错（例如无法解析工具输出），而不是练习未通过
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 测试代码与模板不一致的文件，相对课程目录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tampered: Vec<String>,
+    /// 推断不出模板版本、无法检查测试代码是否被修改时的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tamper_unverified: Option<String>,
+    /// 评测各步骤完整输出的日志文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+    /// 练习尚未完成的依据，如 `// I AM NOT DONE` 标记或 `todo!()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfinished: Option<String>,
+}
+
+impl ExerciseResult {
+    /// 是否是 `course` 课程中名为 `name` 的练习；旧版结果没有课程，只比较名称
+    pub fn same_exercise(&self, course: &str, name: &str) -> bool {
+        self.name == name && (self.course.is_empty() || self.course == course)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Statistics {
+    pub total_exercations: usize,
+    pub total_succeeds: usize,
+    pub total_failures: usize,
+    pub total_time: u64,
+    /// 所有练习中运行的测试数（不含忽略的测试），用于按测试计分
+    #[serde(default)]
+    pub total_tests: usize,
+    #[serde(default)]
+    pub passed_tests: usize,
+    /// 评测出错的练习数，已计入 total_failures
+    #[serde(default)]
+    pub total_errors: usize,
+    /// 测试代码被修改的练习数
+    #[serde(default)]
+    pub total_tampered: usize,
+    /// 尚未完成的练习数，已计入 total_failures
+    #[serde(default)]
+    pub total_unfinished: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradeResult {
+    pub exercises: Vec<ExerciseResult>,
+    pub statistics: Statistics,
+}
+
+impl GradeResult {
+    /// 由各练习的结果汇总统计信息，`total_time` 为评测用时（秒）
+    pub fn new(exercises: Vec<ExerciseResult>, total_time: u64) -> Self {
+        let total_succeeds = exercises.iter().filter(|e| e.result).count();
+        let tests = exercises
+            .iter()
+            .flat_map(|e| &e.tests)
+            .filter(|t| t.status != TestStatus::Ignored);
+        let total_tests = tests.clone().count();
+        let passed_tests = tests.filter(|t| t.status == TestStatus::Passed).count();
+        GradeResult {
+            statistics: Statistics {
+                total_exercations: exercises.len(),
+                total_succeeds,
+                total_failures: exercises.len() - total_succeeds,
+                total_time,
+                total_tests,
+                passed_tests,
+                total_errors: exercises.iter().filter(|e| e.error.is_some()).count(),
+                total_tampered: exercises.iter().filter(|e| !e.tampered.is_empty()).count(),
+                total_unfinished: exercises.iter().filter(|e| e.unfinished.is_some()).count(),
+            },
+            exercises,
+        }
+    }
+}
+
+/// 评测课程的选项，所有课程共用
+#[derive(Clone, Debug, Default)]
+pub struct Grader {
+    pub sandbox: Sandbox,
+    pub cxx: CxxOptions,
+    /// 测试代码与模板不一致时的处理方式
+    pub tamper: TamperPolicy,
+    /// 判断 rustlings 练习尚未完成的依据
+    pub unfinished: UnfinishedCheck,
+    /// 用标准测试替换 rustlings 练习中的测试后再评测
+    pub reference_tests: bool,
+    /// 工作区根目录，编译出的程序和换上标准测试的练习放在其中的 target 目录下
+    pub workspace_root: PathBuf,
+    /// 本次评测的日志目录，为 None 时不记录评测日志，例如监视模式
+    pub log: Option<RunLog>,
+    /// 返回的错误信息使用的语言，评测结果中的文字始终为中文原文
+    pub lang: Lang,
+}
+
+impl Grader {
+    /// 评测一个课程中选中的练习，按课程顺序返回各练习的结果
+    ///
+    /// 按课程名称选择评测器，其他课程按目录内容识别语言，识别不出时按 rustlings 方式评测。
+    pub fn grade_course(
+        &self,
+        course: &str,
+        course_path: &Path,
+        selection: &CourseSelection,
+        reporter: &dyn Reporter,
+    ) -> Result<Vec<ExerciseResult>> {
+        let _span = info_span!("course", course = %course).entered();
+        let mut results = match CourseKind::of(course, course_path) {
+            CourseKind::LearningLm => self.eval_learning_lm(course_path, selection, reporter),
+            CourseKind::LearningCxx => self.eval_learning_cxx(course_path, selection, reporter),
+            CourseKind::Generic(language) => {
+                eval_generic(self, language, course_path, selection, reporter)
+            }
+            CourseKind::Rustlings => self.eval_rustlings(course_path, selection, reporter),
+        }
+        .map_err(|e| self.lang.localize_error(e))?;
+        for result in &mut results {
+            result.course = course.to_string();
+        }
+        Ok(results)
+    }
+
+    /// 练习的评测日志，没有设置日志目录时不记录
+    pub fn exercise_log(&self, course_path: &Path, exercise: &str) -> ExerciseLog {
+        self.log
+            .as_ref()
+            .map(|run| run.exercise(course_path, exercise))
+            .unwrap_or_default()
+    }
+
+    /// 评测learning-lm-rs项目
+    fn eval_learning_lm(
+        &self,
+        lm_path: &Path,
+        selection: &CourseSelection,
+        reporter: &dyn Reporter,
+    ) -> Result<Vec<ExerciseResult>> {
+        reporter.report(Event::Course(Evaluator::LearningLm));
+
+        let manifest_path = lm_path.join("Cargo.toml");
+        if !manifest_path.exists() {
+            warn!(path = %manifest_path.display(), "找不到 learning-lm-rs/Cargo.toml 文件");
+            return Ok(Vec::new());
+        }
+
+        let discover = debug_span!("discover").entered();
+        // 测试按所有练习文件归属，只评测选中的
+        let all_exercises = learning_lm_exercises(lm_path)?;
+        let lm_exercises: Vec<&PathBuf> = all_exercises
+            .iter()
+            .filter(|relative| selection.matches(&learning_lm_exercise_name(relative), relative))
+            .collect();
+        debug!(count = lm_exercises.len(), "找到练习");
+        drop(discover);
+        reporter.report(Event::Found(lm_exercises.len()));
+        if lm_exercises.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // 所有练习共用一次 cargo test，日志也共用一份
+        let tests_span = info_span!("exercise", exercise = %SHARED_LOG).entered();
+        let log = self.exercise_log(lm_path, SHARED_LOG);
+        let run = run_learning_lm_tests(lm_path, &self.sandbox, &log, reporter)?;
+        let log = log.finish();
+        drop(tests_span);
+        let tamper = TamperCheck::load(lm_path, self.tamper)?;
+
+        let mut exercise_results = Vec::new();
+        for relative in lm_exercises.iter() {
+            let mut result = learning_lm_file_result(&run, relative, &all_exercises);
+            result.log = log.clone();
+            let result = tamper.apply(result, relative)?;
+            reporter.report(Event::Graded(&result));
+            exercise_results.push(result);
+        }
+        Ok(exercise_results)
+    }
+
+    /// 评测learning-cxx项目，逐个构建并运行每个练习的 xmake 目标
+    fn eval_learning_cxx(
+        &self,
+        course_path: &Path,
+        selection: &CourseSelection,
+        reporter: &dyn Reporter,
+    ) -> Result<Vec<ExerciseResult>> {
+        reporter.report(Event::Course(Evaluator::LearningCxx(self.cxx.backend)));
+
+        if self.cxx.backend == CxxBackend::Xmake && !command_available("xmake") {
+            bail!(msg!(
+                "找不到 xmake，请先运行 cargo xtask setup 安装，或使用 --cxx-backend direct 直接调用编译器"
+            ));
+        }
+
+        let discover = debug_span!("discover").entered();
+        let exercises = cxx_exercises(course_path)?;
+        debug!(count = exercises.len(), "找到练习");
+        drop(discover);
+        if exercises.is_empty() {
+            bail!(msg!(
+                "在 {} 中没有找到练习目录 (exercises/NN_*)",
+                course_path.display()
+            ));
+        }
+        let exercises: Vec<CxxExercise> = exercises
+            .into_iter()
+            .filter(|exercise| {
+                selection.matches(
+                    &exercise.name,
+                    exercise
+                        .dir
+                        .strip_prefix(course_path)
+                        .unwrap_or(&exercise.dir),
+                )
+            })
+            .collect();
+        reporter.report(Event::Found(exercises.len()));
+
+        let tamper = TamperCheck::load(course_path, self.tamper)?;
+        let mut exercise_results = Vec::new();
+        for exercise in exercises {
+            let _span = info_span!("exercise", exercise = %exercise.name).entered();
+            let log = self.exercise_log(course_path, &exercise.name);
+            let mut result = grade_cxx_exercise(self, course_path, &exercise, &log, reporter)?;
+            result.log = log.finish();
+            let result = tamper.apply(result, &exercise.dir)?;
+            reporter.report(Event::Graded(&result));
+            exercise_results.push(result);
+        }
+        Ok(exercise_results)
+    }
+
+    /// 评测rustlings或其他项目
+    fn eval_rustlings(
+        &self,
+        course_path: &Path,
+        selection: &CourseSelection,
+        reporter: &dyn Reporter,
+    ) -> Result<Vec<ExerciseResult>> {
+        let mut course = RustCourse::load(course_path)?;
+        course.set_workspace_root(&self.workspace_root);
+        course.check_unfinished(self.unfinished);
+        // 使用标准测试时学员对测试的修改不影响评分，不再检查
+        let tamper_policy = if self.reference_tests {
+            course.use_reference_tests()?;
+            TamperPolicy::Off
+        } else {
+            self.tamper
+        };
+        let tamper = TamperCheck::load(course_path, tamper_policy)?;
+        reporter.report(Event::Course(Evaluator::Rustlings {
+            cargo: course.uses_cargo(),
+        }));
+
+        // 处理 Rustlings 或其他非 learning-lm-rs 项目
+        let discover = debug_span!("discover").entered();
+        let mut exercise_files = find_exercise_files(course_path)?;
+        sort_by_course_order(course_path, &mut exercise_files)?;
+        exercise_files.retain(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let relative = path.strip_prefix(course_path).unwrap_or(path);
+            selection.matches(&name, relative)
+        });
+        debug!(count = exercise_files.len(), "找到练习");
+        drop(discover);
+        reporter.report(Event::Found(exercise_files.len()));
+
+        let mut exercise_results = Vec::new();
+        for exercise_path in exercise_files.iter() {
+            let name = exercise_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            let _span = info_span!("exercise", exercise = %name).entered();
+            let log = self.exercise_log(course_path, &name);
+            let mut result = course.grade(exercise_path, &self.sandbox, &log, reporter)?;
+            result.log = log.finish();
+            let result = tamper.apply(result, exercise_path)?;
+            reporter.report(Event::Graded(&result));
+            exercise_results.push(result);
+        }
+        Ok(exercise_results)
+    }
+}
+
+/// 用本次的评测结果替换上一次结果中同一课程的同名练习
+pub fn merge_results(previous: &Path, current: Vec<ExerciseResult>) -> Result<Vec<ExerciseResult>> {
+    if !previous.exists() {
+        return Ok(current);
+    }
+    let content = fs::read_to_string(previous).context(msg!("读取 {} 失败", previous.display()))?;
+    let mut merged = serde_json::from_str::<GradeResult>(&content)
+        .context(msg!("解析 {} 失败", previous.display()))?
+        .exercises;
+    for result in current {
+        match merged
+            .iter_mut()
+            .find(|e| e.same_exercise(&result.course, &result.name))
+        {
+            Some(existing) => *existing = result,
+            None => merged.push(result),
+        }
+    }
+    Ok(merged)
+}
+
+/// 报告命令的标准输出和标准错误，空的部分不报告
+pub fn report_output(reporter: &dyn Reporter, stdout: &[u8], stderr: &[u8]) {
+    for content in [stdout, stderr] {
+        if !content.is_empty() {
+            reporter.report(Event::Output(&String::from_utf8_lossy(content)));
+        }
+    }
+}
+
+/// 报告测试的原始输出；JSON 输出已解析为各个测试，不再报告
+pub fn report_test_output(
+    reporter: &dyn Reporter,
+    output: &std::process::Output,
+    tests: &[TestResult],
+    json: bool,
+) {
+    if tests.is_empty() || !json {
+        report_output(reporter, &output.stdout, &output.stderr);
+    }
+}
+
+/// 编译未通过：以前几条诊断作为失败原因；没有解析出诊断时退回原始输出
+pub fn compile_failure(
+    exercise_name: String,
+    diagnostics: Vec<Diagnostic>,
+    output: &std::process::Output,
+) -> ExerciseResult {
+    let reason = diagnostics_reason(&diagnostics)
+        .unwrap_or_else(|| failure_reason(&output.stdout, &output.stderr));
+    ExerciseResult {
+        name: exercise_name,
+        result: false,
+        reason: Some(reason),
+        diagnostics,
+        ..Default::default()
+    }
+}
+
+/// 运行 learning-lm-rs 的全部测试，结果中包含所有测试
+fn run_learning_lm_tests(
+    lm_path: &Path,
+    sandbox: &Sandbox,
+    log: &ExerciseLog,
+    reporter: &dyn Reporter,
+) -> Result<ExerciseResult> {
+    let manifest_path = lm_path.join("Cargo.toml");
+
+    reporter.report(Event::Running("cargo test --release"));
+    let run = run_libtest(
+        || {
+            let mut command = Command::new("cargo");
+            command
+                .arg("test")
+                .arg("--manifest-path")
+                .arg(&manifest_path)
+                .arg("--release")
+                .arg("--")
+                .current_dir(lm_path);
+            sandbox.command(command, &cargo_writable(&lm_path.join("target")))
+        },
+        msg!("learning-lm-rs 测试"),
+        log,
+    )?;
+
+    let success = run.output.status.success();
+    report_test_output(reporter, &run.output, &run.tests, run.json);
+
+    let reason = (!success).then(|| {
+        tests_reason(&run.tests).unwrap_or_else(|| {
+            signal_reason(
+                &run.output.status,
+                failure_reason(&run.output.stdout, &run.output.stderr),
+            )
+        })
+    });
+    Ok(ExerciseResult {
+        name: "learning-lm-rs".to_string(),
+        result: success,
+        reason,
+        tests: run.tests,
+        ..Default::default()
+    })
+}
+
+/// learning-lm-rs 中单个源文件的结果，只按该模块下的测试（如 `operators::tests::test_silu`）计分
+///
+/// 子模块的测试只计入子模块所在的练习文件，见 [`test_owner`]。编译失败、没有运行任何测试时
+/// 沿用整个测试套件的结果；运行了测试但模块下没有测试时记为评测出错，以免按其他模块的结果计分。
+fn learning_lm_file_result(
+    run: &ExerciseResult,
+    relative: &Path,
+    exercises: &[PathBuf],
+) -> ExerciseResult {
+    let name = learning_lm_exercise_name(relative);
+    if run.tests.is_empty() {
+        return ExerciseResult {
+            name,
+            ..run.clone()
+        };
+    }
+
+    let tests: Vec<TestResult> = run
+        .tests
+        .iter()
+        .filter(|test| test_owner(&test.name, exercises) == Some(relative))
+        .cloned()
+        .collect();
+    if tests.is_empty() {
+        let module = module_path(relative);
+        return infra_error(name, msg!("没有找到模块 {} 下的测试", module));
+    }
+
+    let result = tests.iter().all(|test| test.status != TestStatus::Failed);
+    let reason = if result { None } else { tests_reason(&tests) };
+    ExerciseResult {
+        name,
+        result,
+        reason,
+        tests,
+        ..Default::default()
+    }
+}
+
+/// 评测单个 learning-cxx 练习
+fn grade_cxx_exercise(
+    grader: &Grader,
+    course_path: &Path,
+    exercise: &CxxExercise,
+    log: &ExerciseLog,
+    reporter: &dyn Reporter,
+) -> Result<ExerciseResult> {
+    reporter.report(Event::Exercise(&exercise.name));
+    match grader.cxx.backend {
+        CxxBackend::Xmake => {
+            grade_cxx_with_xmake(course_path, exercise, &grader.sandbox, log, reporter)
+        }
+        CxxBackend::Direct => grade_cxx_direct(grader, course_path, exercise, log, reporter),
+    }
+}
+
+/// 先 `xmake build` 再 `xmake run` 练习对应的目标
+///
+/// 构建失败时记录编译错误；构建失败却解析不出编译错误、或 xmake 本身出错时记为评测出错。
+fn grade_cxx_with_xmake(
+    course_path: &Path,
+    exercise: &CxxExercise,
+    sandbox: &Sandbox,
+    log: &ExerciseLog,
+    reporter: &dyn Reporter,
+) -> Result<ExerciseResult> {
+    // xmake 会在课程目录下写入 build 和 .xmake，在用户目录下写入 ~/.xmake；
+    // 沙箱中的全局目录改到 build 下，以免学员的 xmake.lua 改写 ~/.xmake 中的规则和插件
+    let writable = vec![course_path.join("build"), course_path.join(".xmake")];
+    let xmake = |action: &str| {
+        let mut command = Command::new("xmake");
+        command
+            .arg(action)
+            .arg(&exercise.name)
+            .current_dir(course_path);
+        if sandbox.bwrap {
+            let build = course_path.join("build");
+            command.env(
+                "XMAKE_GLOBALDIR",
+                std::path::absolute(&build).unwrap_or(build),
+            );
+        }
+        sandbox
+            .command(command, &writable)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .logged_output(log, &format!("xmake {}", action))
+            .context(msg!("运行 xmake {} {} 失败", action, exercise.name))
+    };
+
+    let build = xmake("build")?;
+    let build_text = strip_ansi(&format!(
+        "{}{}",
+        String::from_utf8_lossy(&build.stdout),
+        String::from_utf8_lossy(&build.stderr)
+    ));
+    reporter.report(Event::Output(&build_text));
+    if !build.status.success() {
+        let diagnostics = parse_gcc_output(&build_text);
+        if !diagnostics.is_empty() {
+            return Ok(compile_failure(exercise.name.clone(), diagnostics, &build));
+        }
+        let error =
+            if build_text.contains("unknown target") || build_text.contains("target not found") {
+                msg!("xmake 中没有目标 {}", exercise.name)
+            } else {
+                msg!(
+                    "无法解析 xmake build 的输出: {}",
+                    failure_reason(&build.stdout, &build.stderr)
+                )
+            };
+        return Ok(infra_error(exercise.name.clone(), error));
+    }
+
+    let run = xmake("run")?;
+    Ok(native_run_result(&exercise.name, &run, reporter))
+}
+
+/// 直接用 g++ 或 clang++ 编译练习目录中的所有 .cpp 文件并运行
+fn grade_cxx_direct(
+    grader: &Grader,
+    course_path: &Path,
+    exercise: &CxxExercise,
+    log: &ExerciseLog,
+    reporter: &dyn Reporter,
+) -> Result<ExerciseResult> {
+    let compiler = find_compiler("CXX", &["g++", "clang++"]).context(msg!(
+        "找不到 C++ 编译器，请安装 g++ 或 clang++，或通过 CXX 环境变量指定"
+    ))?;
+    let mut flags = vec![format!("-std={}", grader.cxx.std)];
+    if let Ok(extra) = std::env::var("CXXFLAGS") {
+        flags.extend(extra.split_whitespace().map(String::from));
+    }
+    let toolchain = NativeToolchain {
+        compiler: &compiler,
+        extension: "cpp",
+        flags: &flags,
+        output_dir: &grader.workspace_root.join(NATIVE_DIR),
+    };
+    grade_native(
+        course_path,
+        &exercise.name,
+        &exercise.dir,
+        &toolchain,
+        &grader.sandbox,
+        log,
+        reporter,
+    )
+}
+
+/// 编译出的 C/C++ 练习程序的目录，相对工作区根目录
+pub const NATIVE_DIR: &str = "target/native";
+
+/// 编译 C/C++ 练习的编译器、源文件扩展名、编译选项和存放编译结果的目录
+pub struct NativeToolchain<'a> {
+    pub compiler: &'a str,
+    pub extension: &'a str,
+    pub flags: &'a [String],
+    pub output_dir: &'a Path,
+}
+
+/// 编译练习目录中指定扩展名的源文件并运行，C 和 C++ 共用
+///
+/// 头文件搜索路径包括练习目录、课程的 exercises 目录和 include 目录（如果存在）。
+/// 编译出的程序放在工具链指定的目录下，在课程目录中运行。
+pub fn grade_native(
+    course_path: &Path,
+    name: &str,
+    dir: &Path,
+    toolchain: &NativeToolchain,
+    sandbox: &Sandbox,
+    log: &ExerciseLog,
+    reporter: &dyn Reporter,
+) -> Result<ExerciseResult> {
+    let NativeToolchain {
+        compiler,
+        extension,
+        flags,
+        output_dir,
+    } = *toolchain;
+    let mut sources: Vec<PathBuf> = fs::read_dir(dir)
+        .context(msg!("无法读取目录: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    sources.sort();
+    if sources.is_empty() {
+        return Ok(infra_error(
+            name.to_string(),
+            msg!("{} 中没有 .{} 文件", dir.display(), extension),
+        ));
+    }
+
+    fs::create_dir_all(output_dir).context(msg!("创建目录 {} 失败", output_dir.display()))?;
+    let binary = output_dir.join(name);
+
+    let mut command = Command::new(compiler);
+    command.arg("-fdiagnostics-color=never").arg("-I").arg(dir);
+    for include in [course_path.join("exercises"), course_path.join("include")] {
+        if include.is_dir() {
+            command.arg("-I").arg(include);
+        }
+    }
+    let build = command
+        .args(&sources)
+        .args(flags)
+        .arg("-o")
+        .arg(&binary)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(log, "build")
+        .context(msg!("运行 {} 失败", compiler))?;
+
+    report_output(reporter, &[], &build.stderr);
+    if !build.status.success() {
+        let diagnostics = parse_gcc_output(&String::from_utf8_lossy(&build.stderr));
+        if diagnostics.is_empty() {
+            let error = msg!(
+                "无法解析 {} 的输出: {}",
+                compiler,
+                failure_reason(&build.stdout, &build.stderr)
+            );
+            return Ok(infra_error(name.to_string(), error));
+        }
+        return Ok(compile_failure(name.to_string(), diagnostics, &build));
+    }
+
+    let mut command = Command::new(&binary);
+    command.current_dir(course_path);
+    let run = sandbox
+        .command(command, &[])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(log, "run")
+        .context(msg!("运行练习 {} 失败", name))?;
+    Ok(native_run_result(name, &run, reporter))
+}
+
+/// 按退出码判断练习是否通过，未通过时以断言或 panic 信息作为原因
+pub fn native_run_result(
+    name: &str,
+    run: &std::process::Output,
+    reporter: &dyn Reporter,
+) -> ExerciseResult {
+    let success = run.status.success();
+    let stdout = strip_ansi(&String::from_utf8_lossy(&run.stdout));
+    let stderr = strip_ansi(&String::from_utf8_lossy(&run.stderr));
+    report_output(reporter, stdout.as_bytes(), stderr.as_bytes());
+
+    let reason = (!success).then(|| {
+        signal_reason(
+            &run.status,
+            failure_reason(stdout.as_bytes(), stderr.as_bytes()),
+        )
+    });
+    ExerciseResult {
+        name: name.to_string(),
+        result: success,
+        reason,
+        ..Default::default()
+    }
+}
+
+/// 依次尝试环境变量（如 CXX、CC）指定的编译器和候选编译器
+pub fn find_compiler(env_var: &str, candidates: &[&str]) -> Option<String> {
+    let from_env = std::env::var(env_var)
+        .ok()
+        .filter(|compiler| !compiler.is_empty());
+    from_env
+        .into_iter()
+        .chain(candidates.iter().map(|compiler| compiler.to_string()))
+        .find(|compiler| command_available(compiler))
+}
+
+/// 命令能否以 `--version` 正常运行
+pub fn command_available(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// 评测工具本身出错，练习的结果未知，按未通过计并单独报告
+pub fn infra_error(name: String, error: String) -> ExerciseResult {
+    ExerciseResult {
+        name,
+        result: false,
+        error: Some(error),
+        ..Default::default()
+    }
+}
+
+/// 进程被信号终止时在失败原因前注明信号，沙箱中超出 CPU 时间限制会收到 SIGXCPU
+pub fn signal_reason(status: &std::process::ExitStatus, reason: String) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            let killed = match signal {
+                9 => msg!("进程被信号 {} 终止，可能超出了资源或时间限制", signal),
+                24 => msg!("进程被信号 {} 终止，超出了 CPU 时间限制", signal),
+                _ => msg!("进程被信号 {} 终止", signal),
+            };
+            return if reason == msg!("未知原因") {
+                killed
+            } else {
+                format!("{}\n{}", killed, reason)
+            };
+        }
+    }
+    reason
+}
+
+/// 从编译器或测试输出中提取简短的失败原因：第一条错误或 panic 信息
+pub fn failure_reason(stdout: &[u8], stderr: &[u8]) -> String {
+    const MAX_LINES: usize = 12;
+    let stdout = String::from_utf8_lossy(stdout);
+    let stderr = String::from_utf8_lossy(stderr);
+
+    for text in [&stderr, &stdout] {
+        let lines: Vec<&str> = text.lines().collect();
+        let start = lines.iter().position(|line| {
+            line.starts_with("error")
+                || line.contains("panicked at")
+                || line.contains("Assertion")
+                || line.starts_with("memory allocation of")
+        });
+        if let Some(start) = start {
+            // learning-cxx 的断言信息在空行之后才给出断言条件和说明
+            let is_assertion = lines[start].contains("Assertion");
+            return lines[start..]
+                .iter()
+                .filter(|line| !(is_assertion && line.trim().is_empty()))
+                .take_while(|line| {
+                    !line.trim().is_empty()
+                        && !line.starts_with("stack backtrace:")
+                        && !line.starts_with("note:")
+                })
+                .take(MAX_LINES)
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+    }
+
+    // 找不到明确的错误时，取标准错误的最后几行
+    let tail: Vec<&str> = stderr
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let tail = &tail[tail.len().saturating_sub(MAX_LINES)..];
+    if tail.is_empty() {
+        msg!("未知原因").to_string()
+    } else {
+        tail.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(course: &str, name: &str, passed: bool) -> ExerciseResult {
+        ExerciseResult {
+            name: name.to_string(),
+            course: course.to_string(),
+            result: passed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_results_keys_by_course_and_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let previous = dir.path().join("eval_result.json");
+        let old = GradeResult::new(
+            vec![
+                result("a", "intro1", false),
+                result("b", "intro1", true),
+                result("", "legacy", false),
+            ],
+            0,
+        );
+        fs::write(&previous, serde_json::to_string(&old).unwrap()).unwrap();
+
+        let merged = merge_results(
+            &previous,
+            vec![result("a", "intro1", true), result("c", "legacy", true)],
+        )
+        .unwrap();
+        let summary: Vec<(&str, &str, bool)> = merged
+            .iter()
+            .map(|e| (e.course.as_str(), e.name.as_str(), e.result))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "intro1", true),
+                ("b", "intro1", true),
+                ("c", "legacy", true)
+            ]
+        );
+    }
+
+    fn test(name: &str, status: TestStatus) -> TestResult {
+        TestResult {
+            name: name.to_string(),
+            status,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn learning_lm_files_are_graded_by_their_own_tests() {
+        let exercises = [
+            PathBuf::from("src/model/mod.rs"),
+            PathBuf::from("src/model/attention.rs"),
+            PathBuf::from("src/operators.rs"),
+        ];
+        let run = ExerciseResult {
+            name: "learning-lm-rs".to_string(),
+            result: false,
+            tests: vec![
+                test("model::tests::load", TestStatus::Passed),
+                test("model::attention::tests::mask", TestStatus::Failed),
+            ],
+            ..Default::default()
+        };
+
+        let model = learning_lm_file_result(&run, &exercises[0], &exercises);
+        assert_eq!(model.name, "model/mod.rs");
+        assert!(model.result);
+        assert_eq!(model.tests.len(), 1);
+
+        let attention = learning_lm_file_result(&run, &exercises[1], &exercises);
+        assert!(!attention.result);
+        assert_eq!(attention.tests[0].name, "model::attention::tests::mask");
+
+        // 模块下没有测试时不沿用整个测试套件的结果
+        let operators = learning_lm_file_result(&run, &exercises[2], &exercises);
+        assert!(!operators.result);
+        assert!(operators.tests.is_empty());
+        assert_eq!(
+            operators.error.as_deref(),
+            Some("没有找到模块 operators 下的测试")
+        );
+    }
+
+    #[test]
+    fn learning_lm_build_failure_applies_to_every_file() {
+        let run = ExerciseResult {
+            name: "learning-lm-rs".to_string(),
+            result: false,
+            reason: Some("编译失败".to_string()),
+            ..Default::default()
+        };
+        let exercises = [PathBuf::from("src/operators.rs")];
+        let result = learning_lm_file_result(&run, &exercises[0], &exercises);
+        assert_eq!(result.name, "operators.rs");
+        assert_eq!(result.reason.as_deref(), Some("编译失败"));
+        assert_eq!(result.error, None);
+    }
+}
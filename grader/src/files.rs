@@ -0,0 +1,34 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::msg;
+
+/// 列出课程目录下的所有文件（相对路径），跳过 .git 和 target
+pub fn course_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || (e.file_name() != ".git" && e.file_name() != "target"))
+    {
+        let entry = entry.context(msg!("遍历课程目录失败"))?;
+        if entry.file_type().is_file() {
+            files.push(entry.path().strip_prefix(dir)?.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// 递归复制目录，跳过 .git 和 target
+pub fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for relative in course_files(from)? {
+        let target = to.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).context(msg!("创建目录 {} 失败", parent.display()))?;
+        }
+        fs::copy(from.join(&relative), &target)
+            .context(msg!("复制文件 {} 失败", relative.display()))?;
+    }
+    Ok(())
+}
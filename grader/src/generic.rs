@@ -0,0 +1,826 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::eval::{
+    ExerciseResult, Grader, NATIVE_DIR, NativeToolchain, command_available, find_compiler,
+    grade_native, report_output,
+};
+use crate::files::course_files;
+use crate::libtest::{TestResult, TestStatus, tests_reason};
+use crate::logs::{LoggedOutput, SHARED_LOG};
+use crate::msg;
+use crate::report::{Evaluator, Event, Reporter};
+use crate::selection::CourseSelection;
+use tracing::{debug, debug_span, info_span};
+
+/// 没有专门评测器的课程，按目录内容识别的语言
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    C,
+    Python,
+    Go,
+}
+
+impl Language {
+    /// 识别课程语言，识别不出时返回 None，由 rustlings 方式评测
+    ///
+    /// - Go: 课程根目录有 go.mod
+    /// - Python: 有 pytest.ini、pyproject.toml、conftest.py 或 tests/test_*.py
+    /// - C: exercises 下的练习目录中有 .c 文件
+    pub fn detect(course_path: &Path) -> Option<Self> {
+        if course_path.join("go.mod").is_file() {
+            return Some(Language::Go);
+        }
+        let python_markers = ["pytest.ini", "pyproject.toml", "conftest.py"];
+        if python_markers
+            .iter()
+            .any(|marker| course_path.join(marker).is_file())
+            || !python_test_files(&course_path.join("tests")).is_empty()
+        {
+            return Some(Language::Python);
+        }
+        if !c_exercises(course_path).is_empty() {
+            return Some(Language::C);
+        }
+        None
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::C => "C",
+            Language::Python => "Python",
+            Language::Go => "Go",
+        }
+    }
+}
+
+/// 按识别出的语言评测课程
+pub fn eval_generic(
+    grader: &Grader,
+    language: Language,
+    course_path: &Path,
+    selection: &CourseSelection,
+    reporter: &dyn Reporter,
+) -> Result<Vec<ExerciseResult>> {
+    reporter.report(Event::Course(Evaluator::Generic(language)));
+    match language {
+        Language::C => eval_c(grader, course_path, selection, reporter),
+        Language::Python => eval_python(grader, course_path, selection, reporter),
+        Language::Go => eval_go(grader, course_path, selection, reporter),
+    }
+}
+
+/// 识别出语言的课程中的一个练习，名称与评测结果中的一致
+pub struct GenericExercise {
+    pub name: String,
+    /// 练习目录（C、Go）或测试文件（Python）
+    pub path: PathBuf,
+}
+
+/// 按课程顺序列出练习，与评测时划分练习的方式相同
+///
+/// - C: `exercises/` 下每个含有 .c 文件的目录
+/// - Python: 每个 `test_*.py` 或 `*_test.py` 测试文件，名称为相对课程目录的路径
+/// - Go: 每个含有 `_test.go` 文件的包，名称为相对模块根目录的路径，根目录为 `.`
+pub fn generic_exercises(language: Language, course_path: &Path) -> Result<Vec<GenericExercise>> {
+    let exercises = match language {
+        Language::C => c_exercises(course_path)
+            .into_iter()
+            .map(|dir| GenericExercise {
+                name: dir
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                path: dir,
+            })
+            .collect(),
+        Language::Python => course_files(course_path)?
+            .into_iter()
+            .filter(|relative| !ignored_dir(relative, &[".", "__pycache__", "venv"]))
+            .filter(|relative| {
+                let name = relative.file_name().unwrap_or_default().to_string_lossy();
+                name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py"))
+            })
+            .map(|relative| GenericExercise {
+                name: relative.to_string_lossy().replace('\\', "/"),
+                path: course_path.join(relative),
+            })
+            .collect(),
+        Language::Go => {
+            let mut packages: Vec<PathBuf> = course_files(course_path)?
+                .into_iter()
+                .filter(|relative| !ignored_dir(relative, &[".", "_", "vendor", "testdata"]))
+                .filter(|relative| relative.to_string_lossy().ends_with("_test.go"))
+                .map(|relative| relative.parent().unwrap_or(Path::new("")).to_path_buf())
+                .collect();
+            packages.sort();
+            packages.dedup();
+            packages
+                .into_iter()
+                .map(|relative| GenericExercise {
+                    name: if relative.as_os_str().is_empty() {
+                        ".".to_string()
+                    } else {
+                        relative.to_string_lossy().replace('\\', "/")
+                    },
+                    path: course_path.join(relative),
+                })
+                .collect()
+        }
+    };
+    Ok(exercises)
+}
+
+/// 相对路径中是否有目录以 `prefixes` 之一开头，例如隐藏目录和 vendor 目录
+fn ignored_dir(relative: &Path, prefixes: &[&str]) -> bool {
+    let Some(parent) = relative.parent() else {
+        return false;
+    };
+    parent.components().any(|component| {
+        let name = component.as_os_str().to_string_lossy();
+        prefixes.iter().any(|prefix| name.starts_with(prefix))
+    })
+}
+
+/// C 课程的练习：`exercises/` 下每个含有 .c 文件的目录，按目录名排序
+fn c_exercises(course_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(course_path.join("exercises")) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            fs::read_dir(path).is_ok_and(|files| {
+                files
+                    .filter_map(|file| file.ok())
+                    .any(|file| file.path().extension().is_some_and(|ext| ext == "c"))
+            })
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// 用 CC、gcc 或 clang 编译每个练习目录并运行，退出码为 0 即通过
+fn eval_c(
+    grader: &Grader,
+    course_path: &Path,
+    selection: &CourseSelection,
+    reporter: &dyn Reporter,
+) -> Result<Vec<ExerciseResult>> {
+    let compiler = find_compiler("CC", &["gcc", "clang"]).context(msg!(
+        "找不到 C 编译器，请安装 gcc 或 clang，或通过 CC 环境变量指定"
+    ))?;
+    let mut flags: Vec<String> = std::env::var("CFLAGS")
+        .map(|flags| flags.split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+    flags.push("-lm".to_string());
+
+    let discover = debug_span!("discover").entered();
+    let exercises = c_exercises(course_path);
+    debug!(count = exercises.len(), "找到练习");
+    drop(discover);
+    let exercises: Vec<(String, PathBuf)> = exercises
+        .into_iter()
+        .map(|dir| {
+            let name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            (name, dir)
+        })
+        .filter(|(name, dir)| selection.matches(name, dir.strip_prefix(course_path).unwrap_or(dir)))
+        .collect();
+    reporter.report(Event::Found(exercises.len()));
+
+    let output_dir = grader.workspace_root.join(NATIVE_DIR);
+    let mut results = Vec::new();
+    for (name, dir) in exercises {
+        reporter.report(Event::Exercise(&name));
+        let toolchain = NativeToolchain {
+            compiler: &compiler,
+            extension: "c",
+            flags: &flags,
+            output_dir: &output_dir,
+        };
+        let _span = info_span!("exercise", exercise = %name).entered();
+        let log = grader.exercise_log(course_path, &name);
+        let mut result = grade_native(
+            course_path,
+            &name,
+            &dir,
+            &toolchain,
+            &grader.sandbox,
+            &log,
+            reporter,
+        )?;
+        result.log = log.finish();
+        reporter.report(Event::Graded(&result));
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// 课程目录（或其下 tests 目录）中的 test_*.py 文件
+fn python_test_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("test_") && name.ends_with(".py")
+        })
+        .collect()
+}
+
+/// pytest-json-report 报告中用到的字段
+#[derive(Deserialize)]
+struct PytestReport {
+    #[serde(default)]
+    tests: Vec<PytestTest>,
+    #[serde(default)]
+    collectors: Vec<PytestCollector>,
+}
+
+#[derive(Deserialize)]
+struct PytestTest {
+    nodeid: String,
+    outcome: String,
+    #[serde(default)]
+    setup: Option<PytestStage>,
+    #[serde(default)]
+    call: Option<PytestStage>,
+}
+
+#[derive(Deserialize)]
+struct PytestStage {
+    #[serde(default)]
+    longrepr: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PytestCollector {
+    nodeid: String,
+    outcome: String,
+    #[serde(default)]
+    longrepr: Option<String>,
+}
+
+/// 用 pytest 和 pytest-json-report 插件运行测试，每个测试文件是一个练习
+fn eval_python(
+    grader: &Grader,
+    course_path: &Path,
+    selection: &CourseSelection,
+    reporter: &dyn Reporter,
+) -> Result<Vec<ExerciseResult>> {
+    let python = std::env::var("PYTHON")
+        .ok()
+        .filter(|python| !python.is_empty())
+        .into_iter()
+        .chain(["python3".to_string(), "python".to_string()])
+        .find(|python| command_available(python))
+        .context(msg!(
+            "找不到 Python，请安装 python3，或通过 PYTHON 环境变量指定"
+        ))?;
+
+    let report_dir = tempfile::tempdir().context(msg!("创建临时目录失败"))?;
+    let report_path = report_dir.path().join("report.json");
+    reporter.report(Event::Running(&format!("{} -m pytest", python)));
+    let mut command = Command::new(&python);
+    command
+        .args([
+            "-m",
+            "pytest",
+            "-q",
+            "-p",
+            "no:cacheprovider",
+            "--json-report",
+        ])
+        .arg(format!("--json-report-file={}", report_path.display()))
+        .current_dir(course_path)
+        // 沙箱中课程目录只读，不写 __pycache__
+        .env("PYTHONDONTWRITEBYTECODE", "1");
+    let tests_span = info_span!("exercise", exercise = %SHARED_LOG).entered();
+    let log = grader.exercise_log(course_path, SHARED_LOG);
+    let output = grader
+        .sandbox
+        .command(command, &[report_dir.path().to_path_buf()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(&log, "pytest")
+        .context(msg!("运行 pytest 失败"))?;
+    let log = log.finish();
+    drop(tests_span);
+    report_output(reporter, &output.stdout, &output.stderr);
+
+    if !report_path.exists() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No module named pytest") {
+            bail!(msg!(
+                "没有安装 pytest，请运行: {} -m pip install pytest pytest-json-report",
+                python
+            ));
+        }
+        if stderr.contains("--json-report") {
+            bail!(msg!(
+                "没有安装 pytest-json-report 插件，请运行: {} -m pip install pytest-json-report",
+                python
+            ));
+        }
+        bail!(msg!("pytest 没有生成测试报告:\n{}", stderr.trim()));
+    }
+    let parse = debug_span!("parse").entered();
+    let content = fs::read_to_string(&report_path).context(msg!("读取 pytest 测试报告失败"))?;
+    let report: PytestReport =
+        serde_json::from_str(&content).context(msg!("解析 pytest 测试报告失败"))?;
+
+    let exercises = pytest_results(report);
+    drop(parse);
+
+    let mut results = Vec::new();
+    for mut result in exercises {
+        let file = PathBuf::from(&result.name);
+        let name = file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if !selection.matches(&name, &file) {
+            continue;
+        }
+        result.log = log.clone();
+        reporter.report(Event::Graded(&result));
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// 按测试文件汇总 pytest 报告，练习名称为测试文件的相对路径；收集失败的文件也算作一个练习
+fn pytest_results(report: PytestReport) -> Vec<ExerciseResult> {
+    let mut files: BTreeMap<String, Vec<TestResult>> = BTreeMap::new();
+    let mut collect_errors: BTreeMap<String, String> = BTreeMap::new();
+    for collector in report.collectors {
+        if collector.outcome == "failed" && collector.nodeid.ends_with(".py") {
+            files.entry(collector.nodeid.clone()).or_default();
+            collect_errors.insert(collector.nodeid, collector.longrepr.unwrap_or_default());
+        }
+    }
+    for test in report.tests {
+        let file = test
+            .nodeid
+            .split("::")
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let status = match test.outcome.as_str() {
+            "passed" | "xfailed" => TestStatus::Passed,
+            "skipped" => TestStatus::Ignored,
+            _ => TestStatus::Failed,
+        };
+        let message = (status == TestStatus::Failed)
+            .then(|| {
+                [test.call, test.setup]
+                    .into_iter()
+                    .flatten()
+                    .find_map(|stage| stage.longrepr)
+            })
+            .flatten();
+        let name = test
+            .nodeid
+            .split_once("::")
+            .map_or(test.nodeid.as_str(), |(_, name)| name)
+            .to_string();
+        files.entry(file).or_default().push(TestResult {
+            name,
+            status,
+            message,
+        });
+    }
+
+    files
+        .into_iter()
+        .map(|(file, tests)| match collect_errors.remove(&file) {
+            Some(error) => ExerciseResult {
+                name: file,
+                result: false,
+                reason: Some(format!("{}\n{}", msg!("无法导入测试文件:"), error.trim())),
+                ..Default::default()
+            },
+            None => tests_result(file, tests),
+        })
+        .collect()
+}
+
+/// `go test -json` 输出的一行
+#[derive(Deserialize)]
+struct GoEvent {
+    #[serde(rename = "Action")]
+    action: String,
+    #[serde(rename = "Package", default)]
+    package: String,
+    /// Go 1.24 起编译输出也在事件流中，这些事件只有 ImportPath，例如 `pkg [pkg.test]`
+    #[serde(rename = "ImportPath", default)]
+    import_path: String,
+    #[serde(rename = "Test")]
+    test: Option<String>,
+    #[serde(rename = "Output")]
+    output: Option<String>,
+}
+
+/// 用 `go test -json ./...` 运行测试，每个包是一个练习
+fn eval_go(
+    grader: &Grader,
+    course_path: &Path,
+    selection: &CourseSelection,
+    reporter: &dyn Reporter,
+) -> Result<Vec<ExerciseResult>> {
+    if !go_available() {
+        bail!(msg!("找不到 go 命令，请先安装 Go"));
+    }
+
+    reporter.report(Event::Running("go test -json ./..."));
+    let mut command = Command::new("go");
+    command
+        .args(["test", "-json", "./..."])
+        .current_dir(course_path)
+        // 课程依赖应已在本地模块缓存或 vendor 目录中，不访问网络
+        .env("GOPROXY", "off");
+    let tests_span = info_span!("exercise", exercise = %SHARED_LOG).entered();
+    let log = grader.exercise_log(course_path, SHARED_LOG);
+    let output = grader
+        .sandbox
+        .command(command, &go_cache_dir().into_iter().collect::<Vec<_>>())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(&log, "go test")
+        .context(msg!("运行 go test 失败"))?;
+    let log = log.finish();
+    drop(tests_span);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    report_output(reporter, &[], &output.stderr);
+
+    let module = go_module(course_path)?;
+    let parse = debug_span!("parse").entered();
+    let (packages, test_output) = parse_go_events(&String::from_utf8_lossy(&output.stdout));
+    drop(parse);
+    report_output(reporter, test_output.as_bytes(), &[]);
+
+    let mut results = Vec::new();
+    for (package, state) in packages {
+        let relative = package
+            .strip_prefix(&module)
+            .map(|rest| rest.trim_start_matches('/'))
+            .filter(|rest| !rest.is_empty())
+            .unwrap_or(".")
+            .to_string();
+        if !selection.matches(&relative, Path::new(&relative)) {
+            continue;
+        }
+        let Some(mut result) = go_package_result(relative, &package, state, &stderr) else {
+            continue;
+        };
+        result.log = log.clone();
+        reporter.report(Event::Graded(&result));
+        results.push(result);
+    }
+
+    if results.is_empty() && !output.status.success() && !stderr.trim().is_empty() {
+        bail!(msg!("go test 运行失败:\n{}", stderr.trim()));
+    }
+    Ok(results)
+}
+
+/// 一个 Go 包的测试事件汇总
+#[derive(Default)]
+struct GoPackage {
+    /// 测试名 -> (结果, 输出)
+    tests: BTreeMap<String, (Option<TestStatus>, String)>,
+    /// 不属于某个测试的输出，编译错误也在这里
+    output: String,
+    passed: Option<bool>,
+}
+
+/// 解析 `go test -json` 的事件流，按包汇总测试结果；同时返回所有测试输出
+fn parse_go_events(stdout: &str) -> (BTreeMap<String, GoPackage>, String) {
+    let mut packages: BTreeMap<String, GoPackage> = BTreeMap::new();
+    // 测试的输出按 go test 给出的顺序拼接，作为原始输出报告
+    let mut test_output = String::new();
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<GoEvent>(line) else {
+            continue;
+        };
+        let name = match event.import_path.split_whitespace().next() {
+            Some(import_path) if event.package.is_empty() => import_path,
+            _ => event.package.as_str(),
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let package = packages.entry(name.to_string()).or_default();
+        if event.action == "output" {
+            test_output.push_str(event.output.as_deref().unwrap_or_default());
+        }
+        match event.test {
+            Some(test) => {
+                let entry = package.tests.entry(test).or_default();
+                match event.action.as_str() {
+                    "output" => entry
+                        .1
+                        .push_str(event.output.as_deref().unwrap_or_default()),
+                    "pass" => entry.0 = Some(TestStatus::Passed),
+                    "fail" => entry.0 = Some(TestStatus::Failed),
+                    "skip" => entry.0 = Some(TestStatus::Ignored),
+                    _ => {}
+                }
+            }
+            None => match event.action.as_str() {
+                "output" | "build-output" => package
+                    .output
+                    .push_str(event.output.as_deref().unwrap_or_default()),
+                "pass" | "skip" => package.passed = Some(true),
+                "fail" => package.passed = Some(false),
+                _ => {}
+            },
+        }
+    }
+    (packages, test_output)
+}
+
+/// 一个包的练习结果，没有测试文件的包不算练习
+fn go_package_result(
+    relative: String,
+    package: &str,
+    state: GoPackage,
+    stderr: &str,
+) -> Option<ExerciseResult> {
+    if state.tests.is_empty() && state.output.contains("[no test files]") {
+        return None;
+    }
+
+    let tests: Vec<TestResult> = state
+        .tests
+        .into_iter()
+        .filter(|(test, _)| !test.contains('/'))
+        .map(|(name, (status, output))| TestResult {
+            name,
+            status: status.unwrap_or(TestStatus::Failed),
+            message: (status != Some(TestStatus::Passed))
+                .then(|| output.trim().to_string())
+                .filter(|output| !output.is_empty()),
+        })
+        .collect();
+    let result = if tests.is_empty() && state.passed != Some(true) {
+        // 编译失败时没有测试事件，编译错误在标准错误中以 `# 包名` 开头的段落里
+        let build_errors = go_build_errors(stderr, package);
+        let reason = if !build_errors.is_empty() {
+            build_errors
+        } else if state.output.trim().is_empty() {
+            stderr.trim().to_string()
+        } else {
+            state.output.trim().to_string()
+        };
+        ExerciseResult {
+            name: relative,
+            result: false,
+            reason: Some(reason),
+            ..Default::default()
+        }
+    } else {
+        tests_result(relative, tests)
+    };
+    Some(result)
+}
+
+/// 标准错误中某个包的编译错误段落
+fn go_build_errors(stderr: &str, package: &str) -> String {
+    let header = format!("# {}", package);
+    stderr
+        .lines()
+        .skip_while(|line| line.trim() != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with("# "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// go.mod 中声明的模块路径
+fn go_module(course_path: &Path) -> Result<String> {
+    let go_mod = course_path.join("go.mod");
+    let content = fs::read_to_string(&go_mod).context(msg!("读取 {} 失败", go_mod.display()))?;
+    Ok(content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+/// go 的构建缓存目录，沙箱中需要可写
+fn go_cache_dir() -> Option<PathBuf> {
+    if let Some(cache) = std::env::var_os("GOCACHE") {
+        return Some(PathBuf::from(cache));
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .map(|cache| cache.join("go-build"))
+}
+
+/// go 不支持 `--version`，用 `go version` 检查
+fn go_available() -> bool {
+    Command::new("go")
+        .arg("version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// 根据各个测试的结果给出练习结果
+fn tests_result(name: String, tests: Vec<TestResult>) -> ExerciseResult {
+    let result = !tests.is_empty() && tests.iter().all(|test| test.status != TestStatus::Failed);
+    let reason = if result {
+        None
+    } else {
+        tests_reason(&tests).or_else(|| Some(msg!("没有运行任何测试").to_string()))
+    };
+    ExerciseResult {
+        name,
+        result,
+        reason,
+        tests,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 沙箱中没有 pytest 和 go，以下报告按 pytest-json-report 和 `go test -json` 的文档格式编写
+    /// pytest-json-report 的报告：一个文件有通过、失败、跳过和 xfail 的测试，一个文件导入失败，
+    /// 一个文件的 fixture 出错
+    const PYTEST_REPORT: &str = include_str!("../tests/fixtures/pytest-report.json");
+    /// Go 1.24 之前的 `go test -json`，编译错误在标准错误中
+    const GO_EVENTS: &str = include_str!("../tests/fixtures/go-test.json");
+    const GO_STDERR: &str = include_str!("../tests/fixtures/go-test.err");
+    /// Go 1.24 起编译错误以 build-output 事件出现在标准输出中
+    const GO_BUILD_EVENTS: &str = include_str!("../tests/fixtures/go-test-build.json");
+
+    const MODULE: &str = "example.com/exercises";
+
+    fn statuses(result: &ExerciseResult) -> Vec<(&str, TestStatus)> {
+        result
+            .tests
+            .iter()
+            .map(|test| (test.name.as_str(), test.status))
+            .collect()
+    }
+
+    fn go_results(events: &str, stderr: &str) -> Vec<ExerciseResult> {
+        let (packages, _) = parse_go_events(events);
+        packages
+            .into_iter()
+            .filter_map(|(package, state)| {
+                let relative = package.trim_start_matches(MODULE).trim_start_matches('/');
+                go_package_result(relative.to_string(), &package, state, stderr)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pytest_report_groups_tests_by_file() {
+        let report: PytestReport = serde_json::from_str(PYTEST_REPORT).unwrap();
+        let results = pytest_results(report);
+        let names: Vec<&str> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["test_basics.py", "test_broken.py", "test_fixture.py"]
+        );
+
+        let basics = &results[0];
+        assert!(!basics.result);
+        // xfail 算作通过，跳过的测试不计入总数
+        assert_eq!(
+            statuses(basics),
+            [
+                ("test_add", TestStatus::Failed),
+                ("test_sub", TestStatus::Passed),
+                ("test_later", TestStatus::Ignored),
+                ("test_known_bug", TestStatus::Passed),
+            ]
+        );
+        assert!(basics.tests[2].message.is_none());
+        assert!(basics.tests[3].message.is_none());
+        let reason = basics.reason.as_deref().unwrap();
+        assert!(
+            reason.starts_with("1/3 个测试未通过\ntest_add:\n  def test_add():\n"),
+            "{}",
+            reason
+        );
+        assert!(reason.contains("  E       assert -1 == 5\n"));
+    }
+
+    #[test]
+    fn pytest_import_and_setup_errors() {
+        let report: PytestReport = serde_json::from_str(PYTEST_REPORT).unwrap();
+        let results = pytest_results(report);
+
+        let broken = &results[1];
+        assert!(!broken.result);
+        assert!(broken.tests.is_empty());
+        let reason = broken.reason.as_deref().unwrap();
+        assert!(reason.starts_with("无法导入测试文件:\nImportError while importing"));
+        assert!(reason.ends_with("No module named 'broken'"));
+
+        // fixture 出错时没有 call 阶段，失败信息来自 setup 阶段
+        let fixture = &results[2];
+        assert_eq!(
+            statuses(fixture),
+            [("test_with_fixture", TestStatus::Failed)]
+        );
+        let message = fixture.tests[0].message.as_deref().unwrap();
+        assert!(message.ends_with("test_fixture.py:6: FileNotFoundError"));
+    }
+
+    #[test]
+    fn go_events_group_tests_by_package() {
+        let results = go_results(GO_EVENTS, GO_STDERR);
+        // 没有测试文件的 util 包不算练习
+        let names: Vec<&str> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(names, ["broken", "hello", "strings"]);
+
+        let hello = &results[1];
+        assert!(hello.result);
+        assert_eq!(statuses(hello), [("TestHello", TestStatus::Passed)]);
+        assert!(hello.tests[0].message.is_none());
+
+        // 子测试不单独列出
+        let strings = &results[2];
+        assert!(!strings.result);
+        assert_eq!(
+            statuses(strings),
+            [
+                ("TestLower", TestStatus::Ignored),
+                ("TestUpper", TestStatus::Failed),
+            ]
+        );
+        assert_eq!(
+            strings.tests[0].message.as_deref(),
+            Some(
+                "=== RUN   TestLower\n    strings_test.go:20: not implemented yet\n--- SKIP: TestLower (0.00s)"
+            )
+        );
+        assert!(
+            strings
+                .reason
+                .as_deref()
+                .unwrap()
+                .starts_with("1/1 个测试未通过\nTestUpper:\n")
+        );
+    }
+
+    #[test]
+    fn go_test_output_keeps_order() {
+        let (_, output) = parse_go_events(GO_EVENTS);
+        assert!(output.starts_with("=== RUN   TestHello\n--- PASS: TestHello (0.00s)\nPASS\n"));
+        assert!(output.contains("    --- FAIL: TestUpper/word (0.00s)\n"));
+        assert!(output.ends_with(&format!("FAIL\t{}/broken [build failed]\n", MODULE)));
+    }
+
+    #[test]
+    fn go_build_errors_from_stderr() {
+        let results = go_results(GO_EVENTS, GO_STDERR);
+        let broken = &results[0];
+        assert!(!broken.result);
+        assert!(broken.tests.is_empty());
+        assert_eq!(
+            broken.reason.as_deref(),
+            Some("broken/broken.go:5:9: undefined: answer\nbroken/broken.go:6:2: missing return")
+        );
+        assert_eq!(go_build_errors(GO_STDERR, &format!("{}/hello", MODULE)), "");
+    }
+
+    #[test]
+    fn go_build_output_events() {
+        let results = go_results(GO_BUILD_EVENTS, "");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "broken");
+        let reason = results[0].reason.as_deref().unwrap();
+        assert!(
+            reason.contains("broken/broken.go:5:9: undefined: answer\n"),
+            "{}",
+            reason
+        );
+    }
+}
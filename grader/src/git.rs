@@ -0,0 +1,80 @@
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::msg;
+
+/// 在指定目录执行 git 命令并返回标准输出，命令失败时把标准错误带进错误信息
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .with_context(|| msg!("执行 git {} 失败", args.join(" ")))?;
+
+    if !output.status.success() {
+        bail!(msg!(
+            "git {} 执行失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// 执行 git 命令，只关心是否成功
+pub fn git_ok(dir: &Path, args: &[&str]) -> Result<bool> {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| msg!("执行 git {} 失败", args.join(" ")))?;
+    Ok(status.success())
+}
+
+/// 把 `git diff --name-only` 之类的输出拆成文件列表
+pub fn lines(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 推断学员开始做题时的模板版本
+///
+/// 依次尝试: 与 upstream 分支的共同祖先、学员第一次提交的父提交。两者都推断不出时返回错误，
+/// 不退回到 HEAD：与 HEAD 比较会把学员已提交的修改当成模板。
+pub fn template_rev(course_dir: &Path) -> Result<String> {
+    for branch in ["upstream/master", "upstream/main"] {
+        if git_ok(course_dir, &["rev-parse", "--verify", "--quiet", branch])? {
+            return git(course_dir, &["merge-base", "HEAD", branch]);
+        }
+    }
+
+    if let Ok(email) = git(course_dir, &["config", "user.email"]) {
+        let author = format!("--author=<{}>", email);
+        let learner_commits = lines(&git(
+            course_dir,
+            &["log", "--reverse", "-F", "--format=%H", &author, "HEAD"],
+        )?);
+        if let Some(first) = learner_commits.first() {
+            let parent = format!("{}^", first);
+            if git_ok(course_dir, &["rev-parse", "--verify", "--quiet", &parent])? {
+                return Ok(parent);
+            }
+        }
+    }
+
+    bail!(msg!(
+        "无法确定 {} 的模板版本: 没有 upstream/master 或 upstream/main 分支，也找不到 git config user.email 对应作者的提交；可以用 git remote add upstream <模板仓库地址> 添加模板仓库后再试",
+        course_dir.display()
+    ))
+}
@@ -0,0 +1,300 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+use crate::git::git;
+use crate::msg;
+
+/// `.gitmodules` 中的一个 `[submodule "name"]` 配置节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submodule {
+    pub name: String,
+    pub path: String,
+    pub url: String,
+    pub branch: Option<String>,
+    pub update: Option<String>,
+    /// 未识别的键，写回时原样保留
+    pub extra: Vec<(String, String)>,
+}
+
+/// `.gitmodules` 文件的结构化表示
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitModules {
+    pub submodules: Vec<Submodule>,
+}
+
+impl GitModules {
+    /// 读取 `.gitmodules`，文件不存在时返回空配置
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(path).with_context(|| msg!("读取 {} 文件失败", path.display()))?;
+        Self::parse(&content).with_context(|| msg!("解析 {} 文件失败", path.display()))
+    }
+
+    /// 按 git-config 语法解析 `.gitmodules` 内容，`[submodule "name"]` 以外的配置节被忽略
+    pub fn parse(content: &str) -> Result<Self> {
+        // 先收集原始键值，最后再检查必填项；同名的配置节合并在一起，与 git 一致
+        let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        // 当前所在的子模块配置节，处在其他配置节中时为 None
+        let mut current = None;
+        let mut seen_header = false;
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line_no = index + 1;
+            let line = strip_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let Some(header) = header.strip_suffix(']') else {
+                    bail!(msg!("第 {} 行: 配置节缺少 ']'", line_no));
+                };
+                seen_header = true;
+                current = match submodule_name(header.trim()) {
+                    Some(name) if name.is_empty() => {
+                        bail!(msg!("第 {} 行: 子模块名称为空", line_no));
+                    }
+                    Some(name) => match sections.iter().position(|(n, _)| *n == name) {
+                        Some(index) => Some(index),
+                        None => {
+                            sections.push((name, Vec::new()));
+                            Some(sections.len() - 1)
+                        }
+                    },
+                    None => None,
+                };
+                continue;
+            }
+
+            if !seen_header {
+                bail!(msg!("第 {} 行: 键值对不在任何配置节内", line_no));
+            }
+            let Some(index) = current else {
+                continue;
+            };
+            let entries = &mut sections[index].1;
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), unquote(value.trim())),
+                // git-config 中没有值的键表示 true
+                None => (line, "true".to_string()),
+            };
+            entries.push((key.to_ascii_lowercase(), value));
+        }
+
+        let mut submodules = Vec::new();
+        for (name, entries) in sections {
+            let mut path = None;
+            let mut url = None;
+            let mut branch = None;
+            let mut update = None;
+            let mut extra = Vec::new();
+            for (key, value) in entries {
+                match key.as_str() {
+                    "path" => path = Some(normalize_path(&value)),
+                    "url" => url = Some(value),
+                    "branch" => branch = Some(value),
+                    "update" => update = Some(value),
+                    _ => extra.push((key, value)),
+                }
+            }
+            let Some(path) = path else {
+                bail!(msg!("子模块 {} 缺少 path 配置", name));
+            };
+            let Some(url) = url else {
+                bail!(msg!("子模块 {} 缺少 url 配置", name));
+            };
+            submodules.push(Submodule {
+                name,
+                path,
+                url,
+                branch,
+                update,
+                extra,
+            });
+        }
+
+        Ok(Self { submodules })
+    }
+
+    /// 修改 `path` 文件中子模块 `name` 的地址
+    ///
+    /// 通过 `git config -f` 原地修改，文件中的注释、其他配置节和键的顺序保持不变。
+    pub fn set_url(path: &Path, name: &str, url: &str) -> Result<()> {
+        let file = path.to_string_lossy();
+        let key = format!("submodule.{}.url", name);
+        git(Path::new("."), &["config", "-f", &file, &key, url])
+            .with_context(|| msg!("写入 {} 文件失败", path.display()))?;
+        Ok(())
+    }
+
+    /// 按路径查找子模块，路径比较前会统一分隔符和首尾的 `./`、`/`
+    pub fn find_by_path(&self, path: &str) -> Option<&Submodule> {
+        let path = normalize_path(path);
+        self.submodules.iter().find(|s| s.path == path)
+    }
+}
+
+impl Submodule {
+    /// 判断给定地址与已配置的地址是否指向同一仓库（忽略结尾的 `/` 与 `.git`）
+    pub fn same_url(&self, url: &str) -> bool {
+        fn canonical(url: &str) -> &str {
+            let url = url.trim().trim_end_matches('/');
+            url.strip_suffix(".git").unwrap_or(url)
+        }
+        canonical(&self.url) == canonical(url)
+    }
+}
+
+/// 统一子模块路径的写法，便于比较
+pub fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let path = path.trim_end_matches('/');
+    path.strip_prefix("./").unwrap_or(path).to_string()
+}
+
+/// 去掉引号外的 `#` / `;` 注释
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '#' | ';' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// 配置节标题 `submodule "name"` 中的子模块名称，其他配置节返回 None
+///
+/// 配置节名不区分大小写，名称区分大小写，引号内可以用 `\"` 和 `\\` 转义。
+fn submodule_name(header: &str) -> Option<String> {
+    let section = header.get(..9)?;
+    let rest = &header[9..];
+    if !section.eq_ignore_ascii_case("submodule") || !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let mut chars = rest.trim_start().strip_prefix('"')?.chars();
+    let mut name = String::new();
+    loop {
+        match chars.next()? {
+            '\\' => name.push(chars.next()?),
+            '"' => break,
+            c => name.push(c),
+        }
+    }
+    chars.as_str().trim().is_empty().then_some(name)
+}
+
+/// 去掉值中的引号并处理转义
+fn unquote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {}
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(other) => result.push(other),
+                None => {}
+            },
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_submodules_and_skips_other_sections() {
+        let content = r#"
+# 课程子模块
+[core]
+	bare = false
+[submodule "exercises/rustlings"]
+	path = exercises/rustlings ; 注释
+	url = https://github.com/rust-lang/rustlings.git
+	branch = main
+[submodulefoo "x"]
+	path = x
+[Submodule "learning-cxx"]
+	path = ./exercises/learning-cxx/
+	url = "https://example.com/cxx # fork"
+	ignore = dirty
+"#;
+        let modules = GitModules::parse(content).unwrap();
+        assert_eq!(modules.submodules.len(), 2);
+        let rustlings = modules.find_by_path("exercises/rustlings").unwrap();
+        assert_eq!(rustlings.name, "exercises/rustlings");
+        assert_eq!(rustlings.branch.as_deref(), Some("main"));
+        let cxx = modules.find_by_path("exercises\\learning-cxx").unwrap();
+        assert_eq!(cxx.name, "learning-cxx");
+        assert_eq!(cxx.url, "https://example.com/cxx # fork");
+        assert_eq!(cxx.extra, vec![("ignore".to_string(), "dirty".to_string())]);
+    }
+
+    #[test]
+    fn header_names_keep_quotes_and_brackets() {
+        assert_eq!(
+            submodule_name(r#"submodule "a]\"b""#).as_deref(),
+            Some(r#"a]"b"#)
+        );
+        assert_eq!(submodule_name("submodule \"\"").as_deref(), Some(""));
+        assert_eq!(submodule_name("submodule"), None);
+        assert_eq!(submodule_name("submodule.name"), None);
+        assert_eq!(submodule_name(r#"submodule "a" b"#), None);
+        assert_eq!(submodule_name(r#"remote "origin""#), None);
+    }
+
+    #[test]
+    fn merges_repeated_sections_and_rejects_bad_ones() {
+        let content = "[submodule \"a\"]\n\tpath = a\n[submodule \"a\"]\n\turl = u\n";
+        let modules = GitModules::parse(content).unwrap();
+        assert_eq!(modules.submodules.len(), 1);
+        assert_eq!(modules.submodules[0].url, "u");
+
+        assert!(GitModules::parse("path = a\n").is_err());
+        assert!(GitModules::parse("[submodule \"\"]\n").is_err());
+        assert!(GitModules::parse("[submodule \"a\"]\n\tpath = a\n").is_err());
+    }
+
+    #[test]
+    fn set_url_keeps_comments_and_other_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitmodules");
+        let content = "\
+# 课程子模块，请勿手动修改
+[submodule \"rustlings\"]
+\tpath = exercises/rustlings
+\t# 学员的 fork
+\turl = https://example.com/old.git
+[submodule \"cxx\"]
+\tpath = exercises/cxx
+\turl = https://example.com/cxx.git
+";
+        fs::write(&path, content).unwrap();
+
+        GitModules::set_url(&path, "rustlings", "https://example.com/new.git").unwrap();
+        let updated = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            updated,
+            content.replace("https://example.com/old.git", "https://example.com/new.git")
+        );
+        let modules = GitModules::load(&path).unwrap();
+        assert_eq!(
+            modules.find_by_path("exercises/rustlings").unwrap().url,
+            "https://example.com/new.git"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::sync::OnceLock;
+
+/// 英文消息目录，键为源码中的中文原文
+const EN_CATALOG: &str = include_str!("../locales/en.toml");
+
+/// 消息的语言，评测结果 JSON 的字段名和结构不受影响
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Lang {
+    /// 简体中文
+    #[default]
+    #[cfg_attr(feature = "clap", value(name = "zh-CN"))]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    /// 英文
+    #[cfg_attr(feature = "clap", value(name = "en"))]
+    #[serde(rename = "en")]
+    En,
+}
+
+static EN: OnceLock<HashMap<String, String>> = OnceLock::new();
+static EN_TEMPLATES: OnceLock<Vec<Template>> = OnceLock::new();
+
+/// 不翻译的消息，参数按顺序替换 `{}`
+///
+/// 库中的错误信息和评测结果都用它生成，与界面语言无关，由调用方用 [`Lang::localize_text`] 翻译。
+/// 不带参数时返回 `&'static str`，带参数时返回 `String`。
+#[macro_export]
+macro_rules! msg {
+    ($text:literal) => {
+        $text
+    };
+    ($text:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($text, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+impl Lang {
+    /// 这种语言下的译文，中文或目录中没有译文时返回 None
+    pub fn translate(self, text: &str) -> Option<&'static str> {
+        if self == Lang::ZhCn {
+            return None;
+        }
+        english().get(text).map(String::as_str)
+    }
+
+    /// 翻译一条消息，没有译文时使用原文；译文中可以用 `{0}`、`{1}` 调整参数顺序
+    pub fn tr(self, text: &'static str) -> &'static str {
+        self.translate(text).unwrap_or(text)
+    }
+
+    /// 逐行翻译 [`msg!`] 生成的文字，编译器输出等其他文字保持原样
+    pub fn localize_text(self, text: &str) -> Cow<'_, str> {
+        if self == Lang::ZhCn {
+            return Cow::Borrowed(text);
+        }
+        let templates = EN_TEMPLATES.get_or_init(|| templates(english()));
+        localize_lines(text, english(), templates)
+    }
+
+    /// 逐条翻译错误信息及其上下文，用于库返回给调用方的错误
+    pub fn localize_error(self, error: anyhow::Error) -> anyhow::Error {
+        if self == Lang::ZhCn {
+            return error;
+        }
+        let mut messages: Vec<String> = error
+            .chain()
+            .map(|cause| self.localize_text(&cause.to_string()).into_owned())
+            .collect();
+        let root = anyhow::anyhow!(messages.pop().unwrap_or_default());
+        messages
+            .into_iter()
+            .rev()
+            .fold(root, |error, message| error.context(message))
+    }
+}
+
+fn english() -> &'static HashMap<String, String> {
+    EN.get_or_init(|| toml::from_str(EN_CATALOG).unwrap_or_default())
+}
+
+/// 目录中带参数的消息，如 `练习尚未完成: {}`
+struct Template {
+    pattern: Regex,
+    /// 原文中参数以外的文字长度，多个消息都能匹配时取最长的
+    literal: usize,
+    translation: String,
+}
+
+fn templates(catalog: &HashMap<String, String>) -> Vec<Template> {
+    catalog
+        .iter()
+        .filter(|(text, _)| text.contains("{}") && !text.contains('\n') && !text.contains("{{"))
+        .filter_map(|(text, translation)| {
+            let parts: Vec<String> = text.split("{}").map(regex::escape).collect();
+            let literal = text.replace("{}", "");
+            // 只有参数和标点的消息（如 `{}:`）会匹配任意文字
+            if literal.is_ascii() {
+                return None;
+            }
+            Some(Template {
+                pattern: Regex::new(&format!("^{}$", parts.join("(.*?)"))).ok()?,
+                literal: literal.chars().count(),
+                translation: translation.clone(),
+            })
+        })
+        .collect()
+}
+
+fn localize_lines<'a>(
+    text: &'a str,
+    catalog: &HashMap<String, String>,
+    templates: &[Template],
+) -> Cow<'a, str> {
+    let lines: Vec<Cow<str>> = text
+        .split('\n')
+        .map(|line| localize_line(line, catalog, templates))
+        .collect();
+    if lines.iter().all(|line| matches!(line, Cow::Borrowed(_))) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(lines.join("\n"))
+}
+
+fn localize_line<'a>(
+    line: &'a str,
+    catalog: &HashMap<String, String>,
+    templates: &[Template],
+) -> Cow<'a, str> {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    if text.is_ascii() {
+        return Cow::Borrowed(line);
+    }
+    if let Some(translation) = catalog.get(text) {
+        return Cow::Owned(format!("{}{}", indent, translation));
+    }
+    let matched = templates
+        .iter()
+        .filter_map(|template| Some((template, template.pattern.captures(text)?)))
+        .max_by_key(|(template, _)| template.literal);
+    let Some((template, captures)) = matched else {
+        return Cow::Borrowed(line);
+    };
+    // 参数本身也可能是消息，如 `练习尚未完成: 第 3 行有 todo!() 标记`
+    let args: Vec<String> = captures
+        .iter()
+        .skip(1)
+        .map(|arg| {
+            localize_line(arg.map_or("", |arg| arg.as_str()), catalog, templates).into_owned()
+        })
+        .collect();
+    let args: Vec<&dyn Display> = args.iter().map(|arg| arg as &dyn Display).collect();
+    Cow::Owned(format!("{}{}", indent, fill(&template.translation, &args)))
+}
+
+/// 把参数填入消息，`{}` 依次取参数，`{n}` 取第 n 个参数，`{{` 和 `}}` 为花括号本身
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                filled.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                filled.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    index.push(c);
+                }
+                let index = index.parse().unwrap_or_else(|_| {
+                    next += 1;
+                    next - 1
+                });
+                if let Some(arg) = args.get(index) {
+                    let _ = write!(filled, "{}", arg);
+                }
+            }
+            c => filled.push(c),
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_replaces_placeholders_in_order() {
+        assert_eq!(
+            fill("第 {} 行有 {} 标记", &[&3, &"todo!()"]),
+            "第 3 行有 todo!() 标记"
+        );
+    }
+
+    #[test]
+    fn fill_supports_positional_placeholders() {
+        assert_eq!(
+            fill("No .{1} files in {0}", &[&"src", &"c"]),
+            "No .c files in src"
+        );
+        assert_eq!(fill("{1}{0}{}", &[&"a", &"b"]), "baa");
+    }
+
+    #[test]
+    fn fill_keeps_escaped_braces_and_ignores_missing_args() {
+        assert_eq!(fill("{{}} {}", &[&1]), "{} 1");
+        assert_eq!(fill("{} {} {5}", &[&1]), "1  ");
+    }
+
+    #[test]
+    fn msg_fills_without_translating() {
+        assert_eq!(msg!("未知原因"), "未知原因");
+        assert_eq!(msg!("练习尚未完成: {}", "x"), "练习尚未完成: x");
+    }
+
+    #[test]
+    fn localize_translates_messages_line_by_line() {
+        let catalog: HashMap<String, String> = toml::from_str(EN_CATALOG).unwrap();
+        let templates = templates(&catalog);
+        let localize = |text| localize_lines(text, &catalog, &templates).into_owned();
+
+        // 参数中的消息也翻译，学员程序和编译器的输出保持原样
+        assert_eq!(
+            localize("练习尚未完成: 第 3 行有 todo!() 标记"),
+            "Exercise not finished yet: Line 3 has the todo!() marker"
+        );
+        assert_eq!(
+            localize("1/2 个测试未通过\ntests::adds:\n  assertion failed: 结果"),
+            "1/2 tests failed\ntests::adds:\n  assertion failed: 结果"
+        );
+        assert_eq!(localize("  未知原因"), "  unknown reason");
+        assert_eq!(localize("error: 0"), "error: 0");
+        assert!(matches!(
+            localize_lines("error: 0\n中文输出", &catalog, &templates),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn localize_error_translates_every_cause() {
+        let error = anyhow::anyhow!("未知原因").context(msg!("读取 {} 失败", "a.toml"));
+        let localized = Lang::En.localize_error(error);
+        let messages: Vec<String> = localized.chain().map(|cause| cause.to_string()).collect();
+        assert_eq!(messages, ["Failed to read a.toml", "unknown reason"]);
+
+        let error = anyhow::anyhow!("未知原因");
+        assert_eq!(Lang::ZhCn.localize_error(error).to_string(), "未知原因");
+    }
+
+    #[test]
+    fn english_catalog_parses() {
+        // 重复的键会让整个目录解析失败，所有消息都退回中文
+        let catalog: HashMap<String, String> = toml::from_str(EN_CATALOG).unwrap();
+        assert_eq!(catalog["未知原因"], "unknown reason");
+        assert!(catalog.values().all(|text| !text.is_empty()));
+    }
+}
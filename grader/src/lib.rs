@@ -0,0 +1,49 @@
+//! 练习评测库：发现课程中的练习，在沙箱中构建、运行并解析结果，返回结构化的评测结果
+//!
+//! 库本身不向终端输出。评测过程中的进度通过 [`report::Reporter`] 交给调用方，
+//! 运行日志通过 `tracing` 记录，都没有设置时不产生任何输出。工作区目录、评测日志和语言
+//! 都由 [`eval::Grader`] 的字段指定，库不读取当前目录或全局状态。
+//!
+//! ```no_run
+//! use grader::discover::course_dirs;
+//! use grader::eval::{GradeResult, Grader};
+//! use grader::report::Silent;
+//! use grader::selection::Selection;
+//! use std::path::{Path, PathBuf};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let grader = Grader {
+//!     workspace_root: PathBuf::from("."),
+//!     ..Grader::default()
+//! };
+//! let selection = Selection::all();
+//! let mut exercises = Vec::new();
+//! for course in course_dirs(Path::new("exercises"))? {
+//!     let course_selection = selection.for_course(&course.name, &course.path)?;
+//!     exercises.extend(grader.grade_course(&course.name, &course.path, &course_selection, &Silent)?);
+//! }
+//! let result = GradeResult::new(exercises, 0);
+//! println!("{}/{}", result.statistics.total_succeeds, result.statistics.total_exercations);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod course;
+pub mod diagnostics;
+pub mod discover;
+pub mod eval;
+pub mod files;
+pub mod generic;
+pub mod git;
+pub mod gitmodules;
+pub mod i18n;
+pub mod libtest;
+pub mod logs;
+pub mod reference;
+pub mod report;
+pub mod rustlings;
+pub mod sandbox;
+pub mod selection;
+pub mod tamper;
+pub mod test_items;
+pub mod unfinished;
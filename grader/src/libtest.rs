@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Output, Stdio};
+
+use crate::logs::{ExerciseLog, LoggedOutput};
+use crate::msg;
+use tracing::debug_span;
+
+/// 失败原因中最多列出的失败测试数
+const MAX_REASON_TESTS: usize = 3;
+
+/// 稳定版工具链拒绝 `-Z unstable-options` 时 libtest 输出的错误
+const NIGHTLY_ONLY: &str = "only accepted on the nightly compiler";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// 练习中单个 `#[test]` 函数的结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    /// 失败时捕获的输出，通常是 panic 信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 一次测试运行的输出和解析出的各个测试结果
+pub struct TestRun {
+    pub output: Output,
+    pub tests: Vec<TestResult>,
+    /// 是否使用了 libtest 的 JSON 输出，此时 stdout 不适合直接展示
+    pub json: bool,
+}
+
+/// libtest JSON 输出中的一行
+#[derive(Deserialize)]
+struct JsonEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    #[serde(default)]
+    name: String,
+    stdout: Option<String>,
+}
+
+/// 运行 libtest 测试程序，优先使用 JSON 输出，工具链不支持时退回默认输出再解析
+///
+/// `command` 每次调用都返回一个新的命令，测试程序的参数会追加在末尾，
+/// 通过 cargo 运行时应已包含 `--`。
+pub fn run_libtest(
+    mut command: impl FnMut() -> Command,
+    description: &str,
+    log: &ExerciseLog,
+) -> Result<TestRun> {
+    let output = command()
+        .args(["-Z", "unstable-options", "--format", "json"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(log, "test")
+        .context(msg!("运行 {} 失败", description))?;
+
+    if !String::from_utf8_lossy(&output.stderr).contains(NIGHTLY_ONLY) {
+        let tests = parse_json(&String::from_utf8_lossy(&output.stdout));
+        return Ok(TestRun {
+            output,
+            tests,
+            json: true,
+        });
+    }
+
+    let output = command()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_output(log, "test")
+        .context(msg!("运行 {} 失败", description))?;
+    let tests = parse_pretty(&String::from_utf8_lossy(&output.stdout));
+    Ok(TestRun {
+        output,
+        tests,
+        json: false,
+    })
+}
+
+fn parse_json(stdout: &str) -> Vec<TestResult> {
+    let _span = debug_span!("parse").entered();
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JsonEvent>(line).ok())
+        .filter(|event| event.kind == "test")
+        .filter_map(|event| {
+            let status = match event.event.as_str() {
+                "ok" => TestStatus::Passed,
+                "failed" | "timeout" => TestStatus::Failed,
+                "ignored" => TestStatus::Ignored,
+                _ => return None,
+            };
+            let message = event.stdout.as_deref().and_then(clean_message);
+            Some(TestResult {
+                name: event.name,
+                status,
+                message: message.filter(|_| status == TestStatus::Failed),
+            })
+        })
+        .collect()
+}
+
+/// 解析默认输出中的 `test xxx ... ok` 行，以及 `---- xxx stdout ----` 段落中的失败信息
+fn parse_pretty(stdout: &str) -> Vec<TestResult> {
+    let _span = debug_span!("parse").entered();
+    let mut tests = Vec::new();
+    for line in stdout.lines() {
+        let Some((name, status)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        else {
+            continue;
+        };
+        let status = if status == "ok" {
+            TestStatus::Passed
+        } else if status.starts_with("FAILED") {
+            TestStatus::Failed
+        } else if status.starts_with("ignored") {
+            TestStatus::Ignored
+        } else {
+            continue;
+        };
+        tests.push(TestResult {
+            name: name.to_string(),
+            status,
+            message: None,
+        });
+    }
+
+    let mut current: Option<(String, Vec<&str>)> = None;
+    let mut sections = Vec::new();
+    for line in stdout.lines() {
+        let header = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"));
+        if header.is_some() || line == "failures:" || line.starts_with("test result:") {
+            sections.extend(current.take());
+        }
+        if let Some(name) = header {
+            current = Some((name.to_string(), Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    sections.extend(current);
+
+    for (name, lines) in sections {
+        if let Some(test) = tests.iter_mut().find(|test| test.name == name) {
+            test.message = clean_message(&lines.join("\n"));
+        }
+    }
+    tests
+}
+
+/// 去掉 backtrace 及 libtest 附加的 backtrace 提示
+fn clean_message(text: &str) -> Option<String> {
+    let message = text
+        .lines()
+        .take_while(|line| !line.starts_with("stack backtrace:"))
+        .filter(|line| !line.starts_with("note: run with `RUST_BACKTRACE=1`"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    (!message.is_empty()).then_some(message)
+}
+
+/// 用失败的测试拼出失败原因，没有失败的测试时返回 None
+pub fn tests_reason(tests: &[TestResult]) -> Option<String> {
+    let failed: Vec<&TestResult> = tests
+        .iter()
+        .filter(|test| test.status == TestStatus::Failed)
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec![msg!(
+        "{}/{} 个测试未通过",
+        failed.len(),
+        tests
+            .iter()
+            .filter(|test| test.status != TestStatus::Ignored)
+            .count()
+    )];
+    for test in failed.iter().take(MAX_REASON_TESTS) {
+        lines.push(format!("{}:", test.name));
+        if let Some(message) = &test.message {
+            lines.extend(message.lines().map(|line| format!("  {}", line)));
+        }
+    }
+    if failed.len() > MAX_REASON_TESTS {
+        lines.push(msg!(
+            "... 还有 {} 个测试未通过",
+            failed.len() - MAX_REASON_TESTS
+        ));
+    }
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 同一个测试程序（一个通过、两个失败、一个忽略）在 Rust 1.95 下的输出
+    /// `RUSTC_BOOTSTRAP=1` 时 `-Z unstable-options --format json` 的输出
+    const JSON: &str = include_str!("../tests/fixtures/libtest.json");
+    /// 默认输出
+    const PRETTY: &str = include_str!("../tests/fixtures/libtest.txt");
+    /// `RUST_BACKTRACE=1` 时只运行失败测试的默认输出
+    const BACKTRACE: &str = include_str!("../tests/fixtures/libtest-backtrace.txt");
+    /// 稳定版工具链拒绝 `-Z` 时的标准错误
+    const STABLE_STDERR: &str = include_str!("../tests/fixtures/libtest-stable.err");
+
+    fn summary(tests: &[TestResult]) -> Vec<(&str, TestStatus)> {
+        tests
+            .iter()
+            .map(|test| (test.name.as_str(), test.status))
+            .collect()
+    }
+
+    fn check_captured(tests: &[TestResult]) {
+        assert_eq!(
+            summary(tests),
+            [
+                ("tests::adds", TestStatus::Failed),
+                ("tests::prints_then_panics", TestStatus::Failed),
+                ("tests::slow", TestStatus::Ignored),
+                ("tests::zero", TestStatus::Passed),
+            ]
+        );
+
+        let adds = tests[0].message.as_deref().unwrap();
+        assert!(adds.starts_with("thread 'tests::adds'"), "{}", adds);
+        assert!(adds.ends_with(
+            "panicked at tests1.rs:13:9:\nassertion `left == right` failed\n  left: -1\n right: 5"
+        ));
+        // 测试自己的输出保留在 panic 信息之前
+        let panics = tests[1].message.as_deref().unwrap();
+        assert!(panics.starts_with("checking add\n\nthread"), "{}", panics);
+        assert!(panics.ends_with("panicked at tests1.rs:28:9:\nnot implemented"));
+
+        assert!(tests[2].message.is_none());
+        assert!(tests[3].message.is_none());
+    }
+
+    #[test]
+    fn json_output() {
+        check_captured(&parse_json(JSON));
+    }
+
+    #[test]
+    fn pretty_output() {
+        check_captured(&parse_pretty(PRETTY));
+    }
+
+    #[test]
+    fn backtrace_is_stripped() {
+        let tests = parse_pretty(BACKTRACE);
+        assert_eq!(summary(&tests), [("tests::adds", TestStatus::Failed)]);
+        let message = tests[0].message.as_deref().unwrap();
+        assert!(
+            message.ends_with("failed\n  left: -1\n right: 5"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn stable_toolchain_is_detected() {
+        assert!(STABLE_STDERR.contains(NIGHTLY_ONLY));
+    }
+
+    #[test]
+    fn reason_lists_failed_tests() {
+        let tests = parse_pretty(PRETTY);
+        let reason = tests_reason(&tests).unwrap();
+        let lines: Vec<&str> = reason.lines().collect();
+        // 忽略的测试不计入总数
+        assert_eq!(lines[0], "2/3 个测试未通过");
+        assert_eq!(lines[1], "tests::adds:");
+        assert!(lines[2].starts_with("  thread 'tests::adds'"));
+        assert!(reason.contains("\ntests::prints_then_panics:\n  checking add\n"));
+
+        assert_eq!(tests_reason(&tests[2..]), None);
+    }
+
+    #[test]
+    fn reason_counts_tests_beyond_the_limit() {
+        let tests: Vec<TestResult> = (0..5)
+            .map(|i| TestResult {
+                name: format!("t{}", i),
+                status: TestStatus::Failed,
+                message: None,
+            })
+            .collect();
+        let reason = tests_reason(&tests).unwrap();
+        assert!(reason.starts_with("5/5 个测试未通过\nt0:\nt1:\nt2:\n"));
+        assert!(reason.ends_with("\n... 还有 2 个测试未通过"));
+    }
+}
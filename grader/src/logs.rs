@@ -0,0 +1,254 @@
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tracing::{debug, debug_span, warn};
+
+/// 评测日志目录，相对工作区根目录，每次评测一个子目录
+pub const LOG_DIR: &str = "target/xtask-logs";
+
+/// 保留最近几次评测的日志
+const KEPT_RUNS: usize = 20;
+
+/// 课程中多个练习共用一次测试运行时（如 learning-lm-rs 的 cargo test）使用的日志名
+pub const SHARED_LOG: &str = "tests";
+
+/// 一次评测的日志目录，各练习的日志放在其中以课程命名的子目录下
+#[derive(Clone, Debug)]
+pub struct RunLog {
+    root: PathBuf,
+    /// 相对工作区根目录，评测结果中记录的日志路径也相对工作区根目录
+    dir: PathBuf,
+}
+
+impl RunLog {
+    /// 在工作区的 [`LOG_DIR`] 下开始记录本次评测的日志
+    ///
+    /// 日志目录在第一次写入日志时才创建，同时清理较早的日志；没有评测任何练习时
+    /// （例如筛选条件不匹配）不创建目录，也不会挤掉较早的日志。
+    pub fn start(workspace_root: &Path) -> RunLog {
+        let base = Path::new(LOG_DIR);
+        let run_id = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut dir = base.join(&run_id);
+        let mut suffix = 1;
+        while workspace_root.join(&dir).exists() {
+            suffix += 1;
+            dir = base.join(format!("{}-{}", run_id, suffix));
+        }
+        RunLog {
+            root: workspace_root.to_path_buf(),
+            dir,
+        }
+    }
+
+    /// 日志目录，还没有写入日志时不存在
+    pub fn dir(&self) -> PathBuf {
+        self.root.join(&self.dir)
+    }
+
+    /// 练习的日志，之后通过 [`LoggedOutput`] 运行的命令都写入其中
+    pub fn exercise(&self, course_path: &Path, exercise: &str) -> ExerciseLog {
+        let course = course_path.file_name().unwrap_or_default();
+        let file_name = format!("{}.log", exercise.replace(['/', '\\'], "_"));
+        let relative = self.dir.join(course).join(file_name);
+        ExerciseLog {
+            path: Some(self.root.join(&relative)),
+            run_dir: self.dir(),
+            relative: relative.to_string_lossy().replace('\\', "/"),
+            file: RefCell::new(None),
+        }
+    }
+}
+
+/// 单个练习的日志文件，第一次写入时才创建；没有开始记录时不写入，例如监视模式
+#[derive(Debug, Default)]
+pub struct ExerciseLog {
+    path: Option<PathBuf>,
+    /// 本次评测的日志目录，第一次创建时清理较早的日志
+    run_dir: PathBuf,
+    relative: String,
+    file: RefCell<Option<File>>,
+}
+
+impl ExerciseLog {
+    /// 结束练习的日志，返回相对工作区根目录的日志文件；没有运行过命令时返回 None
+    pub fn finish(self) -> Option<String> {
+        self.file.into_inner().is_some().then_some(self.relative)
+    }
+
+    fn write_step(
+        &self,
+        path: &Path,
+        step: &str,
+        command: &Command,
+        output: &io::Result<Output>,
+    ) -> io::Result<()> {
+        let mut file = self.file.borrow_mut();
+        if file.is_none() {
+            let new_run = !self.run_dir.exists();
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if new_run && let Some(base) = self.run_dir.parent() {
+                prune_runs(base);
+            }
+            *file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+        let Some(file) = file.as_mut() else {
+            return Ok(());
+        };
+
+        writeln!(file, "==> {}: {}", step, command_line(command))?;
+        if let Some(dir) = command.get_current_dir() {
+            writeln!(file, "cwd: {}", dir.display())?;
+        }
+        match output {
+            Ok(output) => {
+                writeln!(file, "status: {}", output.status)?;
+                for (name, content) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+                    if content.is_empty() {
+                        continue;
+                    }
+                    writeln!(file, "--- {} ---", name)?;
+                    file.write_all(content)?;
+                    if !content.ends_with(b"\n") {
+                        writeln!(file)?;
+                    }
+                }
+            }
+            Err(e) => writeln!(file, "error: {}", e)?,
+        }
+        writeln!(file)
+    }
+}
+
+/// 运行命令并把完整输出写入练习的日志
+pub trait LoggedOutput {
+    /// 与 [`Command::output`] 相同，`step` 是日志中这一步的名称，如 build、clippy、test
+    fn logged_output(&mut self, log: &ExerciseLog, step: &str) -> io::Result<Output>;
+}
+
+impl LoggedOutput for Command {
+    fn logged_output(&mut self, log: &ExerciseLog, step: &str) -> io::Result<Output> {
+        // 构建和检查计入编译阶段，其余计入运行阶段
+        let _span = if step.ends_with("build") || step == "clippy" {
+            debug_span!("compile", step).entered()
+        } else {
+            debug_span!("run", step).entered()
+        };
+        debug!(command = %command_line(self), "运行命令");
+        let output = self.output();
+        if let Some(path) = &log.path
+            && let Err(e) = log.write_step(path, step, self, &output)
+        {
+            // 日志只用于排查问题，写入失败不影响评测
+            warn!(path = %path.display(), error = %e, "写入评测日志失败");
+        }
+        output
+    }
+}
+
+/// 命令和参数，用于日志
+fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 只保留最近 KEPT_RUNS 次评测的日志，目录名以时间开头，按名称排序即按时间排序
+fn prune_runs(base: &Path) {
+    let Ok(entries) = fs::read_dir(base) else {
+        return;
+    };
+    let mut runs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    runs.sort();
+    let excess = runs.len().saturating_sub(KEPT_RUNS);
+    for run in &runs[..excess] {
+        let _ = fs::remove_dir_all(run);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exercise_log_is_written_under_the_workspace_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let run = RunLog::start(workspace.path());
+        assert!(run.dir().starts_with(workspace.path().join(LOG_DIR)));
+
+        let log = run.exercise(Path::new("exercises/rustlings"), "intro/intro1");
+        Command::new("true").logged_output(&log, "run").unwrap();
+        let relative = log.finish().unwrap();
+        assert!(relative.starts_with("target/xtask-logs/"));
+        assert!(relative.ends_with("/rustlings/intro_intro1.log"));
+        let content = fs::read_to_string(workspace.path().join(&relative)).unwrap();
+        assert!(content.starts_with("==> run: true\n"));
+    }
+
+    #[test]
+    fn run_without_commands_creates_no_directory() {
+        let workspace = tempfile::tempdir().unwrap();
+        let run = RunLog::start(workspace.path());
+        assert_eq!(
+            run.exercise(Path::new("rustlings"), "intro1").finish(),
+            None
+        );
+        assert!(!workspace.path().join(LOG_DIR).exists());
+
+        // 没有开始记录时运行命令也不写日志
+        let log = ExerciseLog::default();
+        Command::new("true").logged_output(&log, "run").unwrap();
+        assert_eq!(log.finish(), None);
+    }
+
+    fn create_runs(base: &Path, count: usize) {
+        for i in 0..count {
+            fs::create_dir_all(base.join(format!("20200101-0000{:02}", i))).unwrap();
+        }
+    }
+
+    fn runs(base: &Path) -> Vec<PathBuf> {
+        let mut runs: Vec<PathBuf> = fs::read_dir(base)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        runs.sort();
+        runs
+    }
+
+    #[test]
+    fn old_runs_are_pruned_when_a_run_is_logged() {
+        let workspace = tempfile::tempdir().unwrap();
+        let base = workspace.path().join(LOG_DIR);
+        create_runs(&base, KEPT_RUNS + 3);
+
+        let run = RunLog::start(workspace.path());
+        assert_eq!(runs(&base).len(), KEPT_RUNS + 3);
+        for exercise in ["intro1", "intro2"] {
+            let log = run.exercise(Path::new("rustlings"), exercise);
+            Command::new("true").logged_output(&log, "run").unwrap();
+        }
+
+        let runs = runs(&base);
+        assert_eq!(runs.len(), KEPT_RUNS);
+        assert_eq!(runs.last(), Some(&run.dir()));
+        assert_eq!(runs[0], base.join("20200101-000004"));
+    }
+
+    #[test]
+    fn existing_run_directories_are_not_reused() {
+        let workspace = tempfile::tempdir().unwrap();
+        let first = RunLog::start(workspace.path());
+        fs::create_dir_all(first.dir()).unwrap();
+        let second = RunLog::start(workspace.path());
+        assert_ne!(first.dir(), second.dir());
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git::{git, git_ok};
+use crate::msg;
+use crate::tamper::course_template_rev;
+use crate::test_items::rust_test_items;
+
+/// 课程提供的标准测试目录，目录结构与课程相同，例如
+/// `reference-tests/exercises/00_intro/intro1.rs` 是 `exercises/00_intro/intro1.rs` 的标准测试
+pub const REFERENCE_DIR: &str = "reference-tests";
+
+/// 换上标准测试后的练习文件存放位置，相对工作区根目录
+const MERGED_DIR: &str = "target/xtask-reference";
+
+/// 标准测试的来源
+enum Source {
+    /// 课程的 reference-tests 目录
+    Dir,
+    /// 课程仓库历史中模板版本的测试模块
+    Template(String),
+}
+
+/// 用标准测试替换学员练习中的测试，让评分不受学员对测试的修改影响
+pub struct ReferenceTests {
+    course_path: PathBuf,
+    source: Source,
+    /// 存放合并后文件的目录，cargo 布局的课程在这里为每个练习生成清单
+    merged_dir: PathBuf,
+}
+
+impl ReferenceTests {
+    /// 优先使用课程的 reference-tests 目录，其次使用课程仓库中的模板版本；都没有时返回 None
+    ///
+    /// 课程是 git 仓库但推断不出模板版本时返回错误，以免把学员自己提交的测试当成标准测试。
+    pub fn load(course_path: &Path, workspace_root: &Path) -> Result<Option<Self>> {
+        let source = if course_path.join(REFERENCE_DIR).is_dir() {
+            Source::Dir
+        } else {
+            let rev = course_template_rev(course_path).map_err(|e| {
+                anyhow!(msg!(
+                    "无法取得标准测试: 课程没有 {} 目录，也无法确定模板版本。{}",
+                    REFERENCE_DIR,
+                    e
+                ))
+            })?;
+            match rev {
+                Some(rev) => Source::Template(rev),
+                None => return Ok(None),
+            }
+        };
+        let course = course_path.file_name().unwrap_or_default();
+        Ok(Some(ReferenceTests {
+            course_path: course_path.to_path_buf(),
+            source,
+            merged_dir: workspace_root.join(MERGED_DIR).join(course),
+        }))
+    }
+
+    /// 练习（相对课程目录）的标准测试代码，没有时返回 None
+    fn tests(&self, relative: &Path) -> Result<Option<String>> {
+        match &self.source {
+            Source::Dir => {
+                let path = self.course_path.join(REFERENCE_DIR).join(relative);
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                let tests =
+                    fs::read_to_string(&path).context(msg!("读取 {} 失败", path.display()))?;
+                Ok(Some(tests))
+            }
+            Source::Template(rev) => {
+                let object = format!(
+                    "{}:./{}",
+                    rev,
+                    relative.to_string_lossy().replace('\\', "/")
+                );
+                if !git_ok(&self.course_path, &["cat-file", "-e", &object])? {
+                    return Ok(None);
+                }
+                let template = git(&self.course_path, &["show", &object])?;
+                let lines: Vec<&str> = template.lines().collect();
+                let tests: Vec<&str> = rust_test_items(&lines)
+                    .into_iter()
+                    .flat_map(|item| lines[item].to_vec())
+                    .collect();
+                Ok((!tests.is_empty()).then(|| tests.join("\n")))
+            }
+        }
+    }
+
+    /// 去掉练习中的测试、换上标准测试，写到 target/xtask-reference 下
+    ///
+    /// 返回合并后的文件；练习没有标准测试时返回 None，按学员的文件评测。
+    pub fn inject(&self, exercise_path: &Path) -> Result<Option<PathBuf>> {
+        let relative = exercise_path
+            .strip_prefix(&self.course_path)
+            .unwrap_or(exercise_path);
+        let Some(tests) = self.tests(relative)? else {
+            return Ok(None);
+        };
+
+        let source = fs::read_to_string(exercise_path)
+            .context(msg!("读取 {} 失败", exercise_path.display()))?;
+        let merged = format!("{}\n\n{}\n", strip_tests(&source).trim_end(), tests.trim());
+
+        let merged_path = self.merged_dir().join(relative);
+        if let Some(parent) = merged_path.parent() {
+            fs::create_dir_all(parent).context(msg!("创建目录 {} 失败", parent.display()))?;
+        }
+        fs::write(&merged_path, merged).context(msg!("写入 {} 失败", merged_path.display()))?;
+        Ok(Some(merged_path))
+    }
+
+    /// 存放合并后文件的目录，cargo 布局的课程在这里为每个练习生成清单
+    pub fn merged_dir(&self) -> &Path {
+        &self.merged_dir
+    }
+}
+
+/// 去掉 `#[cfg(test)]` 和 `#[test]` 标注的条目
+fn strip_tests(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let items = rust_test_items(&lines);
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !items.iter().any(|item| item.contains(index)))
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_tests_keeps_learner_code_after_tricky_tests() {
+        let source = "\
+fn learner() -> char {
+    '}'
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn t() {
+        assert_eq!(super::learner(), '}');
+        assert_eq!(\"{\", \"{\");
+    }
+}
+
+fn helper() -> &'static str {
+    \"}\"
+}
+";
+        assert_eq!(
+            strip_tests(source),
+            "fn learner() -> char {\n    '}'\n}\n\n\nfn helper() -> &'static str {\n    \"}\"\n}"
+        );
+    }
+}
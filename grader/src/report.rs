@@ -0,0 +1,44 @@
+use crate::eval::{CxxBackend, ExerciseResult};
+use crate::generic::Language;
+
+/// 课程使用的评测方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluator {
+    LearningLm,
+    LearningCxx(CxxBackend),
+    /// `cargo` 为 true 时通过课程的 Cargo.toml 构建练习，否则用 rustc 单独编译
+    Rustlings {
+        cargo: bool,
+    },
+    /// 按目录内容识别出语言的课程
+    Generic(Language),
+}
+
+/// 评测过程中的事件，按发生的顺序交给 [`Reporter`]
+#[derive(Clone, Copy, Debug)]
+pub enum Event<'a> {
+    /// 开始评测一个课程
+    Course(Evaluator),
+    /// 筛选后要评测的练习数，共用一次测试运行的课程在运行结束后才逐个给出结果
+    Found(usize),
+    /// 开始评测一个练习
+    Exercise(&'a str),
+    /// 开始运行课程的全部测试，参数为命令
+    Running(&'a str),
+    /// 构建、测试或运行命令的原始输出，完整内容也会写入评测日志
+    Output(&'a str),
+    /// 一个练习的最终结果，已经过测试代码检查
+    Graded(&'a ExerciseResult),
+}
+
+/// 接收评测进度，由调用方决定如何展示
+pub trait Reporter {
+    fn report(&self, event: Event);
+}
+
+/// 忽略所有事件
+pub struct Silent;
+
+impl Reporter for Silent {
+    fn report(&self, _event: Event) {}
+}
@@ -0,0 +1,552 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use crate::course::{CargoBin, CargoManifest, ExerciseMode, InfoFile};
+use crate::diagnostics::parse_compiler_output;
+use crate::eval::{
+    ExerciseResult, compile_failure, failure_reason, native_run_result, report_output,
+    report_test_output, signal_reason,
+};
+use crate::libtest::{TestRun, run_libtest, tests_reason};
+use crate::logs::{ExerciseLog, LoggedOutput};
+use crate::msg;
+use crate::reference::{REFERENCE_DIR, ReferenceTests};
+use crate::report::{Event, Reporter};
+use crate::sandbox::{Sandbox, cargo_writable};
+use crate::unfinished::UnfinishedCheck;
+use tracing::debug;
+
+/// 课程没有 Cargo.toml 或未指定 edition 时使用的 edition
+const DEFAULT_EDITION: &str = "2021";
+
+/// rustlings 类课程：练习的评测方式来自 info.toml，构建方式来自课程的 Cargo.toml
+///
+/// 新版 rustlings 在 Cargo.toml 中把每个练习声明为 `[[bin]]`，
+/// 这些练习通过 cargo 构建，能使用课程的 edition 和依赖；
+/// 其余练习用 rustc 单独编译。
+pub struct RustCourse {
+    path: PathBuf,
+    edition: String,
+    bins: Vec<CargoBin>,
+    modes: HashMap<String, ExerciseMode>,
+    /// 设置后用标准测试替换练习中的测试
+    reference: Option<ReferenceTests>,
+    /// 判断练习尚未完成的依据
+    unfinished: UnfinishedCheck,
+    /// 工作区根目录，用 rustc 编译的练习和换上标准测试的练习放在其中的 target 目录下
+    workspace_root: PathBuf,
+}
+
+impl RustCourse {
+    pub fn load(course_path: &Path) -> Result<Self> {
+        let manifest = CargoManifest::load(course_path)?;
+        let edition = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.edition())
+            .unwrap_or(DEFAULT_EDITION)
+            .to_string();
+        let modes = InfoFile::load(course_path)?
+            .map(|info| {
+                info.exercises
+                    .iter()
+                    .map(|e| (e.name.clone(), e.exercise_mode()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(RustCourse {
+            path: course_path.to_path_buf(),
+            edition,
+            bins: manifest.map(|manifest| manifest.bin).unwrap_or_default(),
+            modes,
+            reference: None,
+            unfinished: UnfinishedCheck::default(),
+            workspace_root: PathBuf::new(),
+        })
+    }
+
+    /// 设置工作区根目录，没有设置时为当前目录
+    pub fn set_workspace_root(&mut self, workspace_root: &Path) {
+        self.workspace_root = workspace_root.to_path_buf();
+    }
+
+    /// 改为用标准测试评测，课程没有提供标准测试时出错
+    pub fn use_reference_tests(&mut self) -> Result<()> {
+        let reference =
+            ReferenceTests::load(&self.path, &self.workspace_root)?.with_context(|| {
+                msg!(
+                    "课程 {} 没有标准测试：需要 {} 目录，或课程本身是带有模板历史的 git 仓库",
+                    self.path.display(),
+                    REFERENCE_DIR
+                )
+            })?;
+        self.reference = Some(reference);
+        Ok(())
+    }
+
+    /// 设置判断练习尚未完成的依据
+    pub fn check_unfinished(&mut self, check: UnfinishedCheck) {
+        self.unfinished = check;
+    }
+
+    /// 是否为用 `[[bin]]` 声明练习的新版布局
+    pub fn uses_cargo(&self) -> bool {
+        !self.bins.is_empty()
+    }
+
+    /// `[[bin]]` 中位于 exercises 目录下的练习文件，按声明顺序排列；不是新版布局时返回 None
+    pub fn exercise_files(&self) -> Option<Vec<PathBuf>> {
+        self.uses_cargo().then(|| {
+            self.bins
+                .iter()
+                .filter(|bin| bin.path.starts_with("exercises"))
+                .map(|bin| self.path.join(&bin.path))
+                .filter(|path| path.is_file())
+                .collect()
+        })
+    }
+
+    /// 练习的评测方式，info.toml 中没有时按路径推断：clippy 目录下的练习先做 clippy 检查
+    fn mode(&self, exercise_path: &Path) -> ExerciseMode {
+        let stem = exercise_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.modes
+            .get(&stem)
+            .copied()
+            .unwrap_or_else(|| ExerciseMode {
+                test: true,
+                clippy: exercise_path.to_string_lossy().contains("clippy"),
+            })
+    }
+
+    /// 练习文件对应的 bin 名称
+    fn bin(&self, exercise_path: &Path) -> Option<&str> {
+        self.bins
+            .iter()
+            .find(|bin| self.path.join(&bin.path) == exercise_path)
+            .map(|bin| bin.name.as_str())
+    }
+
+    /// 评测单个练习文件
+    pub fn grade(
+        &self,
+        exercise_path: &Path,
+        sandbox: &Sandbox,
+        log: &ExerciseLog,
+        reporter: &dyn Reporter,
+    ) -> Result<ExerciseResult> {
+        let exercise_name = exercise_path
+            .file_name()
+            .context(msg!("无法获取文件名"))?
+            .to_string_lossy()
+            .to_string();
+
+        reporter.report(Event::Exercise(&exercise_name));
+
+        // 尚未完成的练习即使能通过编译和测试也不算通过，不再评测
+        let source = fs::read_to_string(exercise_path)
+            .context(msg!("读取 {} 失败", exercise_path.display()))?;
+        if let Some(marker) = self.unfinished.find(&source) {
+            return Ok(ExerciseResult {
+                name: exercise_name,
+                result: false,
+                reason: Some(msg!("练习尚未完成: {}", marker)),
+                unfinished: Some(marker),
+                ..Default::default()
+            });
+        }
+
+        let mode = self.mode(exercise_path);
+        // 只有运行测试的练习才需要换上标准测试
+        let merged = match &self.reference {
+            Some(reference) if mode.test => reference.inject(exercise_path)?,
+            _ => None,
+        };
+        if let Some(merged) = &merged {
+            debug!(path = %merged.display(), "使用标准测试");
+        }
+
+        match self.bin(exercise_path) {
+            Some(bin) => {
+                let manifest = match (&self.reference, &merged) {
+                    (Some(reference), Some(merged)) => {
+                        self.reference_manifest(reference, bin, merged)?
+                    }
+                    _ => self.path.join("Cargo.toml"),
+                };
+                let cargo = CargoTarget {
+                    bin,
+                    manifest: &manifest,
+                };
+                self.grade_with_cargo(exercise_name, &cargo, mode, sandbox, log, reporter)
+            }
+            None => {
+                let source = merged.as_deref().unwrap_or(exercise_path);
+                self.grade_with_rustc(source, exercise_name, mode, sandbox, log, reporter)
+            }
+        }
+    }
+
+    /// 为换上标准测试的练习生成只含这一个 bin 的清单，依赖与课程相同，构建目录仍是课程的 target
+    fn reference_manifest(
+        &self,
+        reference: &ReferenceTests,
+        bin: &str,
+        merged: &Path,
+    ) -> Result<PathBuf> {
+        let course_manifest = self.path.join("Cargo.toml");
+        let content = fs::read_to_string(&course_manifest)
+            .context(msg!("读取 {} 失败", course_manifest.display()))?;
+        let mut manifest: toml::Table =
+            toml::from_str(&content).context(msg!("解析 {} 失败", course_manifest.display()))?;
+
+        let course_path =
+            std::path::absolute(&self.path).context(msg!("无法获取课程目录的绝对路径"))?;
+        let absolute = |value: &mut toml::Value| {
+            if let Some(path) = value.as_str() {
+                *value = toml::Value::String(course_path.join(path).to_string_lossy().to_string());
+            }
+        };
+        // 清单换了位置，课程中的相对路径改为绝对路径
+        for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            let Some(toml::Value::Table(dependencies)) = manifest.get_mut(section) else {
+                continue;
+            };
+            for (_, dependency) in dependencies.iter_mut() {
+                if let Some(path) = dependency.get_mut("path") {
+                    absolute(path);
+                }
+            }
+        }
+        if let Some(toml::Value::Table(package)) = manifest.get_mut("package") {
+            package.remove("workspace");
+            if let Some(build) = package.get_mut("build") {
+                absolute(build);
+            }
+        }
+
+        let mut bin_table = toml::Table::new();
+        bin_table.insert("name".to_string(), bin.into());
+        bin_table.insert("path".to_string(), merged.to_string_lossy().as_ref().into());
+        manifest.insert(
+            "bin".to_string(),
+            toml::Value::Array(vec![bin_table.into()]),
+        );
+        manifest.insert("workspace".to_string(), toml::Table::new().into());
+
+        let dir = reference.merged_dir().join("manifests").join(bin);
+        fs::create_dir_all(&dir).context(msg!("创建目录 {} 失败", dir.display()))?;
+        let manifest_path = dir.join("Cargo.toml");
+        fs::write(&manifest_path, toml::to_string(&manifest)?)
+            .context(msg!("写入 {} 失败", manifest_path.display()))?;
+        // 沿用课程锁定的依赖版本
+        let lock = self.path.join("Cargo.lock");
+        if lock.is_file() {
+            fs::copy(&lock, dir.join("Cargo.lock")).context(msg!("复制 Cargo.lock 失败"))?;
+        }
+        Ok(manifest_path)
+    }
+
+    /// 在课程目录中运行 cargo 子命令，构建脚本和练习都在沙箱中运行
+    fn cargo(&self, subcommand: &str, target: &CargoTarget, sandbox: &Sandbox) -> Command {
+        let target_dir = self.path.join("target");
+        let mut command = Command::new("cargo");
+        command
+            .arg(subcommand)
+            .arg("--manifest-path")
+            .arg(target.manifest)
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .args(["--bin", target.bin])
+            .current_dir(&self.path);
+        let mut writable = cargo_writable(&target_dir);
+        // 标准测试的清单不在课程目录中，cargo 会在旁边写入 Cargo.lock
+        if target.manifest != self.path.join("Cargo.toml")
+            && let Some(dir) = target.manifest.parent()
+        {
+            writable.push(dir.to_path_buf());
+        }
+        sandbox.command(command, &writable)
+    }
+
+    fn grade_with_cargo(
+        &self,
+        exercise_name: String,
+        target: &CargoTarget,
+        mode: ExerciseMode,
+        sandbox: &Sandbox,
+        log: &ExerciseLog,
+        reporter: &dyn Reporter,
+    ) -> Result<ExerciseResult> {
+        if mode.clippy {
+            let output = self
+                .cargo("clippy", target, sandbox)
+                .args(["--message-format=json", "--", "-D", "warnings"])
+                .logged_output(log, "clippy")
+                .context(msg!("运行 cargo clippy 检查 {} 失败", exercise_name))?;
+            if !output.status.success() {
+                return Ok(cargo_compile_failure(exercise_name, &output, reporter));
+            }
+        }
+
+        // 先单独构建，编译错误才能以 JSON 诊断的形式与程序输出分开
+        let build = if mode.test {
+            self.cargo("test", target, sandbox)
+                .args(["--no-run", "--message-format=json"])
+                .logged_output(log, "build")
+        } else {
+            self.cargo("build", target, sandbox)
+                .arg("--message-format=json")
+                .logged_output(log, "build")
+        }
+        .context(msg!("编译练习 {} 失败", exercise_name))?;
+        if !build.status.success() {
+            return Ok(cargo_compile_failure(exercise_name, &build, reporter));
+        }
+
+        if !mode.test {
+            let run = self
+                .cargo("run", target, sandbox)
+                .arg("--quiet")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .logged_output(log, "run")
+                .context(msg!("运行练习 {} 失败", exercise_name))?;
+            return Ok(native_run_result(&exercise_name, &run, reporter));
+        }
+
+        let run = run_libtest(
+            || {
+                let mut command = self.cargo("test", target, sandbox);
+                command.arg("--");
+                command
+            },
+            &msg!("练习 {}", exercise_name),
+            log,
+        )?;
+        Ok(test_result(exercise_name, run, reporter))
+    }
+
+    fn grade_with_rustc(
+        &self,
+        exercise_path: &Path,
+        exercise_name: String,
+        mode: ExerciseMode,
+        sandbox: &Sandbox,
+        log: &ExerciseLog,
+        reporter: &dyn Reporter,
+    ) -> Result<ExerciseResult> {
+        // clippy 练习没有清单，需要放到临时 crate 中用 cargo clippy 检查
+        if mode.clippy {
+            let temp_dir = tempfile::tempdir().context(msg!("创建临时目录失败"))?;
+            let temp_dir_path = temp_dir.path();
+
+            let cargo_toml_content = format!(
+                r#"[package]
+name = "clippy_check"
+version = "0.1.0"
+edition = "{}"
+
+[[bin]]
+name = "clippy_check"
+path = "src/main.rs"
+"#,
+                self.edition
+            );
+            let cargo_toml_path = temp_dir_path.join("Cargo.toml");
+            fs::write(&cargo_toml_path, cargo_toml_content)
+                .context(msg!("写入 Cargo.toml 失败"))?;
+
+            let src_dir = temp_dir_path.join("src");
+            fs::create_dir(&src_dir).context(msg!("创建 src 目录失败"))?;
+
+            let exercise_content =
+                fs::read_to_string(exercise_path).context(msg!("读取练习文件失败"))?;
+            fs::write(src_dir.join("main.rs"), exercise_content)
+                .context(msg!("写入 main.rs 失败"))?;
+
+            let clippy_output = Command::new("cargo")
+                .arg("clippy")
+                .arg("--manifest-path")
+                .arg(&cargo_toml_path)
+                .arg("--message-format=json")
+                .args(["--", "-D", "warnings"])
+                .current_dir(temp_dir_path)
+                .logged_output(log, "clippy")
+                .context(msg!("运行 cargo clippy 检查 {} 失败", exercise_name))?;
+            if !clippy_output.status.success() {
+                return Ok(cargo_compile_failure(
+                    exercise_name,
+                    &clippy_output,
+                    reporter,
+                ));
+            }
+        }
+
+        let output_dir = self.workspace_root.join("target").join("debug");
+        fs::create_dir_all(&output_dir).context(msg!("创建目录 {} 失败", output_dir.display()))?;
+        let binary = output_dir.join(&exercise_name);
+        let mut rustc = Command::new("rustc");
+        rustc.arg(exercise_path);
+        if mode.test {
+            rustc.arg("--test");
+        }
+        let compile_output = rustc
+            .args(["--edition", &self.edition])
+            .arg("--error-format=json")
+            .arg("-o")
+            .arg(&binary)
+            .logged_output(log, "build")
+            .context(msg!("编译练习 {} 失败", exercise_name))?;
+
+        if !compile_output.status.success() {
+            let compiler = parse_compiler_output(&compile_output.stderr);
+            reporter.report(Event::Output(&compiler.rendered));
+            return Ok(compile_failure(
+                exercise_name,
+                compiler.diagnostics,
+                &compile_output,
+            ));
+        }
+
+        if !mode.test {
+            let run = sandbox
+                .command(Command::new(&binary), &[])
+                .logged_output(log, "run")
+                .context(msg!("运行练习 {} 失败", exercise_name))?;
+            return Ok(native_run_result(&exercise_name, &run, reporter));
+        }
+
+        let run = run_libtest(
+            || sandbox.command(Command::new(&binary), &[]),
+            &msg!("练习 {}", exercise_name),
+            log,
+        )?;
+        Ok(test_result(exercise_name, run, reporter))
+    }
+}
+
+/// 用 cargo 构建的练习：bin 名称和所在的清单
+struct CargoTarget<'a> {
+    bin: &'a str,
+    manifest: &'a Path,
+}
+
+/// cargo 以 JSON 输出的编译或 clippy 检查未通过
+fn cargo_compile_failure(
+    exercise_name: String,
+    output: &Output,
+    reporter: &dyn Reporter,
+) -> ExerciseResult {
+    let compiler = parse_compiler_output(&output.stdout);
+    report_output(reporter, compiler.rendered.as_bytes(), &output.stderr);
+    compile_failure(exercise_name, compiler.diagnostics, output)
+}
+
+/// 按测试运行结果给出练习结果，未通过时以失败的测试作为原因
+fn test_result(exercise_name: String, run: TestRun, reporter: &dyn Reporter) -> ExerciseResult {
+    let success = run.output.status.success();
+    report_test_output(reporter, &run.output, &run.tests, run.json);
+
+    let reason = (!success).then(|| {
+        tests_reason(&run.tests).unwrap_or_else(|| {
+            signal_reason(
+                &run.output.status,
+                failure_reason(&run.output.stdout, &run.output.stderr),
+            )
+        })
+    });
+    ExerciseResult {
+        name: exercise_name,
+        result: success,
+        reason,
+        tests: run.tests,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::RunLog;
+    use crate::report::Silent;
+
+    const MANIFEST: &str = r#"[package]
+name = "course"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[[bin]]
+name = "exercise"
+path = "exercises/exercise.rs"
+"#;
+
+    const EXERCISE: &str = r#"fn main() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn passes() {}
+}
+"#;
+
+    /// 评测日志中依次运行的步骤，重复运行的同一步只算一次
+    fn logged_steps(workspace_root: &Path, log: ExerciseLog) -> Vec<String> {
+        let relative = log.finish().unwrap();
+        let content = fs::read_to_string(workspace_root.join(relative)).unwrap();
+        let mut steps: Vec<String> = content
+            .lines()
+            .filter_map(|line| line.strip_prefix("==> "))
+            .filter_map(|line| line.split(':').next())
+            .map(str::to_string)
+            .collect();
+        steps.dedup();
+        steps
+    }
+
+    #[test]
+    fn cargo_exercises_run_the_steps_of_their_mode() {
+        let workspace = tempfile::tempdir().unwrap();
+        let course_path = workspace.path().join("course");
+        fs::create_dir_all(course_path.join("exercises")).unwrap();
+        fs::write(course_path.join("Cargo.toml"), MANIFEST).unwrap();
+        let exercise_path = course_path.join("exercises/exercise.rs");
+        fs::write(&exercise_path, EXERCISE).unwrap();
+
+        let cases = [
+            (r#"mode = "compile""#, ["build", "run"].as_slice()),
+            (r#"mode = "clippy""#, &["clippy", "build", "run"]),
+            (r#"mode = "test""#, &["build", "test"]),
+            ("", &["build", "test"]),
+            ("test = false", &["build", "run"]),
+            ("strict_clippy = true", &["clippy", "build", "test"]),
+            (
+                "test = false\nstrict_clippy = true",
+                &["clippy", "build", "run"],
+            ),
+        ];
+        for (settings, expected) in cases {
+            let info = format!("[[exercises]]\nname = \"exercise\"\n{}\n", settings);
+            fs::write(course_path.join("info.toml"), info).unwrap();
+            let mut course = RustCourse::load(&course_path).unwrap();
+            course.set_workspace_root(workspace.path());
+
+            let log = RunLog::start(workspace.path()).exercise(&course_path, "exercise");
+            let result = course
+                .grade(&exercise_path, &Sandbox::default(), &log, &Silent)
+                .unwrap();
+            assert!(result.result, "{:?}: {:?}", settings, result.reason);
+            assert_eq!(
+                logged_steps(workspace.path(), log),
+                expected,
+                "{:?}",
+                settings
+            );
+        }
+    }
+}
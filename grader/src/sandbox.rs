@@ -0,0 +1,325 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// 沙箱的资源限制，通过 shell 的 ulimit 设置，对所有平台都有效
+#[derive(Clone, Debug)]
+pub struct Limits {
+    /// 每个进程的内存上限（MiB）
+    pub memory_mb: u64,
+    /// 每个进程的 CPU 时间上限（秒）
+    pub cpu_seconds: u64,
+    /// 进程数上限，只在 bubblewrap 沙箱中生效
+    ///
+    /// `ulimit -u` 统计的是用户的全部进程，而不只是沙箱中的进程。不使用 bubblewrap 时，
+    /// 桌面或共用的 CI 用户本身的进程就可能超过上限，让练习创建进程失败（EAGAIN），所以不设置。
+    pub processes: u64,
+}
+
+/// 运行学员代码的方式：不隔离、只限制资源，或者用 bubblewrap 隔离文件系统和网络并限制资源
+#[derive(Clone, Debug, Default)]
+pub struct Sandbox {
+    /// 资源限制，为 None 时不限制
+    pub limits: Option<Limits>,
+    /// 用 bubblewrap 隔离文件系统和网络，需要先用 [`bwrap_usable`] 确认能够使用
+    pub bwrap: bool,
+    /// 命令的时间上限（秒），需要先用 [`timeout_usable`] 确认有 timeout 命令
+    pub timeout: Option<u64>,
+    /// cargo 构建的并行数
+    pub jobs: Option<usize>,
+}
+
+impl Sandbox {
+    /// 把会执行学员代码的命令放进沙箱
+    ///
+    /// 命令的程序、参数、环境变量和工作目录会被保留，标准输入输出需要在此之后设置。
+    /// `writable` 是沙箱中仍可写入的目录（如构建目录、缓存目录）或已有的文件，目录不存在时先创建；
+    /// 其余文件系统只读，/tmp 是私有的临时目录。设置了时间上限时，超时的命令被 SIGKILL 终止。
+    pub fn command(&self, mut command: Command, writable: &[PathBuf]) -> Command {
+        if let Some(jobs) = self.jobs {
+            command.env("CARGO_BUILD_JOBS", jobs.to_string());
+        }
+        if self.limits.is_none() && self.timeout.is_none() {
+            return command;
+        }
+
+        let mut wrapped = if self.bwrap {
+            let mut bwrap = Command::new("bwrap");
+            bwrap
+                .args(["--ro-bind", "/", "/"])
+                .args(["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"]);
+            for dir in writable {
+                let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.clone());
+                if dir.is_file() || fs::create_dir_all(&dir).is_ok() {
+                    bwrap.arg("--bind").arg(&dir).arg(&dir);
+                }
+            }
+            bwrap.args(["--unshare-all", "--die-with-parent", "--new-session"]);
+            if let Some(dir) = command.get_current_dir() {
+                let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+                bwrap.arg("--chdir").arg(dir);
+            }
+            bwrap.arg("sh");
+            bwrap
+        } else {
+            Command::new("sh")
+        };
+
+        let mut script = String::new();
+        if let Some(limits) = &self.limits {
+            script.push_str(&format!(
+                "ulimit -v {} && ulimit -t {} && ",
+                limits.memory_mb * 1024,
+                limits.cpu_seconds
+            ));
+            // bubblewrap 的用户命名空间中进程数单独计算；dash 用 -p 限制进程数，bash 等用 -u
+            if self.bwrap {
+                script.push_str(&format!(
+                    "{{ ulimit -u {0} 2>/dev/null || ulimit -p {0}; }} && ",
+                    limits.processes
+                ));
+            }
+        }
+        match self.timeout {
+            Some(seconds) => script.push_str(&format!("exec timeout -s KILL {} \"$@\"", seconds)),
+            None => script.push_str("exec \"$@\""),
+        }
+        wrapped
+            .arg("-c")
+            .arg(script)
+            .arg("sh")
+            .arg(command.get_program())
+            .args(command.get_args());
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        // 沙箱中没有网络，cargo 只能使用本地已有的依赖
+        if self.limits.is_some() {
+            wrapped.env("CARGO_NET_OFFLINE", "true");
+        }
+        if let Some(dir) = command.get_current_dir() {
+            wrapped.current_dir(dir);
+        }
+        wrapped
+    }
+}
+
+/// CARGO_HOME 中 cargo 构建时需要写入的缓存目录和锁文件
+///
+/// 不能让整个 CARGO_HOME 可写：学员的 build.rs 可以借此改写 bin 下的程序或 config.toml，
+/// 它们之后会在沙箱外运行。
+const CARGO_CACHE_DIRS: [&str; 2] = ["registry", "git"];
+const CARGO_LOCK_FILES: [&str; 3] = [".package-cache", ".package-cache-mutate", ".global-cache"];
+
+/// cargo 在沙箱中需要写入的目录和文件：构建目录，以及 CARGO_HOME 中的缓存和锁文件
+pub fn cargo_writable(target_dir: &Path) -> Vec<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")));
+    cargo_home_writable(target_dir, cargo_home.as_deref())
+}
+
+fn cargo_home_writable(target_dir: &Path, cargo_home: Option<&Path>) -> Vec<PathBuf> {
+    let mut writable = vec![target_dir.to_path_buf()];
+    let Some(cargo_home) = cargo_home else {
+        return writable;
+    };
+    writable.extend(CARGO_CACHE_DIRS.iter().map(|dir| cargo_home.join(dir)));
+    // 只读的位置上无法创建锁文件，先在沙箱外创建好再绑定进去
+    for file in CARGO_LOCK_FILES {
+        let path = cargo_home.join(file);
+        if fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .is_ok()
+        {
+            writable.push(path);
+        }
+    }
+    writable
+}
+
+/// 系统中有 GNU coreutils 的 timeout 命令
+pub fn timeout_usable() -> bool {
+    Command::new("timeout")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// 系统中有 bwrap 且能创建沙箱
+pub fn bwrap_usable() -> bool {
+    Command::new("bwrap")
+        .args(["--ro-bind", "/", "/", "--unshare-all", "true"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn env<'a>(command: &'a Command, key: &str) -> Option<Option<&'a OsStr>> {
+        command
+            .get_envs()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+
+    fn limits() -> Limits {
+        Limits {
+            memory_mb: 512,
+            cpu_seconds: 10,
+            processes: 64,
+        }
+    }
+
+    fn student_command() -> Command {
+        let mut command = Command::new("cargo");
+        command
+            .args(["test", "--release"])
+            .env("RUST_BACKTRACE", "1")
+            .env_remove("RUSTFLAGS")
+            .current_dir("/course");
+        command
+    }
+
+    #[test]
+    fn without_limits_only_the_jobs_are_set() {
+        let sandbox = Sandbox {
+            jobs: Some(2),
+            ..Default::default()
+        };
+        let command = sandbox.command(student_command(), &[]);
+        assert_eq!(command.get_program(), "cargo");
+        assert_eq!(args(&command), ["test", "--release"]);
+        assert_eq!(
+            env(&command, "CARGO_BUILD_JOBS"),
+            Some(Some(OsStr::new("2")))
+        );
+        assert_eq!(env(&command, "CARGO_NET_OFFLINE"), None);
+    }
+
+    #[test]
+    fn limits_are_set_by_the_shell() {
+        let sandbox = Sandbox {
+            limits: Some(limits()),
+            ..Default::default()
+        };
+        let command = sandbox.command(student_command(), &[]);
+        assert_eq!(command.get_program(), "sh");
+        // 不使用 bubblewrap 时不限制进程数
+        assert_eq!(
+            args(&command),
+            [
+                "-c",
+                "ulimit -v 524288 && ulimit -t 10 && exec \"$@\"",
+                "sh",
+                "cargo",
+                "test",
+                "--release"
+            ]
+        );
+        assert_eq!(command.get_current_dir(), Some(Path::new("/course")));
+        assert_eq!(env(&command, "RUST_BACKTRACE"), Some(Some(OsStr::new("1"))));
+        assert_eq!(env(&command, "RUSTFLAGS"), Some(None));
+        assert_eq!(
+            env(&command, "CARGO_NET_OFFLINE"),
+            Some(Some(OsStr::new("true")))
+        );
+    }
+
+    #[test]
+    fn timeout_wraps_the_command_without_limits() {
+        let sandbox = Sandbox {
+            timeout: Some(30),
+            ..Default::default()
+        };
+        let command = sandbox.command(student_command(), &[]);
+        assert_eq!(command.get_program(), "sh");
+        assert_eq!(
+            args(&command)[..3],
+            ["-c", "exec timeout -s KILL 30 \"$@\"", "sh"]
+        );
+        // 只限制时间时仍可以联网
+        assert_eq!(env(&command, "CARGO_NET_OFFLINE"), None);
+    }
+
+    #[test]
+    fn bubblewrap_binds_writable_paths_and_limits_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let lock = dir.path().join(".package-cache");
+        fs::write(&lock, "").unwrap();
+        let sandbox = Sandbox {
+            limits: Some(limits()),
+            bwrap: true,
+            timeout: Some(30),
+            jobs: None,
+        };
+        let command = sandbox.command(student_command(), &[target.clone(), lock.clone()]);
+        assert_eq!(command.get_program(), "bwrap");
+        // 可写的目录事先创建好
+        assert!(target.is_dir());
+
+        let target = target.to_string_lossy();
+        let lock = lock.to_string_lossy();
+        let mut expected: Vec<&str> = vec![
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+        ];
+        expected.extend(["--bind", &target, &target, "--bind", &lock, &lock]);
+        expected.extend(["--unshare-all", "--die-with-parent", "--new-session"]);
+        expected.extend(["--chdir", "/course", "sh", "-c"]);
+        expected.push(
+            "ulimit -v 524288 && ulimit -t 10 && \
+             { ulimit -u 64 2>/dev/null || ulimit -p 64; } && \
+             exec timeout -s KILL 30 \"$@\"",
+        );
+        expected.extend(["sh", "cargo", "test", "--release"]);
+        assert_eq!(args(&command), expected);
+        assert_eq!(
+            env(&command, "CARGO_NET_OFFLINE"),
+            Some(Some(OsStr::new("true")))
+        );
+    }
+
+    #[test]
+    fn cargo_home_exposes_only_caches_and_lock_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        assert_eq!(cargo_home_writable(&target, None), vec![target.clone()]);
+
+        let home = dir.path().join("cargo");
+        fs::create_dir(&home).unwrap();
+        let writable = cargo_home_writable(&target, Some(&home));
+        let mut expected = vec![target, home.join("registry"), home.join("git")];
+        expected.extend(CARGO_LOCK_FILES.iter().map(|file| home.join(file)));
+        assert_eq!(writable, expected);
+        // 锁文件在沙箱外创建，bin 和 config.toml 不可写
+        assert!(home.join(".package-cache").is_file());
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::eval::GradeResult;
+use crate::git::{git, git_ok, lines};
+use crate::msg;
+
+/// eval 的练习筛选条件，对所有评测器生效
+pub struct Selection {
+    exercise: Option<String>,
+    filter: Option<Regex>,
+    /// 筛选条件包含 `/` 时匹配相对路径，否则匹配练习名称
+    filter_path: bool,
+    /// 上一次未通过的练习，为课程和名称
+    failed: Option<HashSet<(String, String)>>,
+    since: Option<String>,
+    /// 只选中相对课程目录为这个路径的练习
+    path: Option<PathBuf>,
+}
+
+/// 针对某个课程展开后的筛选条件，`--since` 需要在课程仓库中求出改动的文件
+pub struct CourseSelection<'a> {
+    selection: &'a Selection,
+    course: String,
+    changed: Option<Vec<PathBuf>>,
+}
+
+impl Selection {
+    /// `filter` 默认按 glob 解析，以 `re:` 开头时按正则表达式解析；
+    /// `only_failed` 时从 `previous` 中读取上一次未通过的练习
+    pub fn new(
+        exercise: Option<String>,
+        filter: Option<&str>,
+        only_failed: bool,
+        since: Option<String>,
+        previous: &Path,
+    ) -> Result<Self> {
+        let filter_regex = match filter {
+            Some(pattern) => {
+                let regex = match pattern.strip_prefix("re:") {
+                    Some(regex) => regex.to_string(),
+                    None => glob_to_regex(pattern),
+                };
+                Some(Regex::new(&regex).context(msg!("无效的筛选条件: {}", pattern))?)
+            }
+            None => None,
+        };
+
+        let failed = if only_failed {
+            if !previous.exists() {
+                bail!(msg!(
+                    "找不到上一次的评测结果 {}，无法使用 --only-failed",
+                    previous.display()
+                ));
+            }
+            let content =
+                fs::read_to_string(previous).context(msg!("读取 {} 失败", previous.display()))?;
+            let result: GradeResult =
+                serde_json::from_str(&content).context(msg!("解析 {} 失败", previous.display()))?;
+            Some(
+                result
+                    .exercises
+                    .into_iter()
+                    .filter(|e| !e.result)
+                    .map(|e| (e.course, e.name))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let filter_path = filter.is_some_and(|pattern| pattern.contains('/'));
+        Ok(Self {
+            exercise,
+            filter: filter_regex,
+            filter_path,
+            failed,
+            since,
+            path: None,
+        })
+    }
+
+    /// 不筛选，评测所有练习
+    pub fn all() -> Self {
+        Self {
+            exercise: None,
+            filter: None,
+            filter_path: false,
+            failed: None,
+            since: None,
+            path: None,
+        }
+    }
+
+    /// 只选中相对课程目录为 `relative` 的一个练习，watch 逐个评测练习时使用
+    pub fn path(relative: &Path) -> Self {
+        Self {
+            path: Some(relative.to_path_buf()),
+            ..Self::all()
+        }
+    }
+
+    /// 是否设置了任何筛选条件
+    pub fn is_active(&self) -> bool {
+        self.exercise.is_some()
+            || self.filter.is_some()
+            || self.failed.is_some()
+            || self.since.is_some()
+            || self.path.is_some()
+    }
+
+    pub fn for_course(&self, course: &str, course_path: &Path) -> Result<CourseSelection<'_>> {
+        let changed = match &self.since {
+            Some(rev) => Some(changed_since(course_path, rev)?),
+            None => None,
+        };
+        Ok(CourseSelection {
+            selection: self,
+            course: course.to_string(),
+            changed,
+        })
+    }
+}
+
+impl CourseSelection<'_> {
+    /// 判断练习是否被选中；`relative` 是练习文件或目录相对课程目录的路径
+    pub fn matches(&self, name: &str, relative: &Path) -> bool {
+        let selection = self.selection;
+        let relative_str = relative.to_string_lossy().replace('\\', "/");
+
+        let exercise_ok = selection.exercise.as_ref().is_none_or(|exercise| {
+            let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+            let number = name.strip_prefix("exercise");
+            name == exercise
+                || stem == exercise
+                || relative_str == *exercise
+                || number.is_some_and(|number| number == exercise)
+        });
+        let filter_ok = selection.filter.as_ref().is_none_or(|filter| {
+            if selection.filter_path {
+                filter.is_match(&relative_str)
+            } else {
+                filter.is_match(name)
+            }
+        });
+        // 旧版评测结果没有课程，只按名称匹配
+        let failed_ok = selection.failed.as_ref().is_none_or(|failed| {
+            failed.contains(&(self.course.clone(), name.to_string()))
+                || failed.contains(&(String::new(), name.to_string()))
+        });
+        let path_ok = selection.path.as_ref().is_none_or(|path| path == relative);
+        let changed_ok = self
+            .changed
+            .as_ref()
+            .is_none_or(|changed| changed.iter().any(|path| path.starts_with(relative)));
+
+        exercise_ok && filter_ok && failed_ok && path_ok && changed_ok
+    }
+}
+
+/// 课程仓库中自 `rev` 以来改动过的文件（含未提交和未跟踪的文件），路径相对课程目录
+fn changed_since(course_path: &Path, rev: &str) -> Result<Vec<PathBuf>> {
+    if !git_ok(course_path, &["rev-parse", "--git-dir"])? {
+        bail!(msg!(
+            "{} 不是 git 仓库，无法使用 --since",
+            course_path.display()
+        ));
+    }
+    let mut changed = lines(&git(
+        course_path,
+        &["diff", "--name-only", "--relative", rev, "--"],
+    )?);
+    changed.extend(lines(&git(
+        course_path,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?));
+    Ok(changed.into_iter().map(PathBuf::from).collect())
+}
+
+/// 把 glob 转为整串匹配的正则表达式，支持 `*`、`?` 和 `[...]`（含 `[a-z]` 范围和 `[!...]` 取反）
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let mut in_class = false;
+    for c in pattern.chars() {
+        match c {
+            '*' if !in_class => regex.push_str(".*"),
+            '?' if !in_class => regex.push('.'),
+            '[' if !in_class => {
+                in_class = true;
+                regex.push('[');
+            }
+            ']' if in_class => {
+                in_class = false;
+                regex.push(']');
+            }
+            '!' if in_class && regex.ends_with('[') => regex.push('^'),
+            // 字符类中的 `-` 表示范围，不能转义；只转义正则字符类里另有含义的字符
+            '\\' | '[' | '&' | '~' if in_class => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ if in_class => regex.push(c),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> Regex {
+        Regex::new(&glob_to_regex(pattern)).unwrap()
+    }
+
+    #[test]
+    fn glob_matches_whole_names() {
+        let regex = glob("variables*");
+        assert!(regex.is_match("variables1"));
+        assert!(regex.is_match("variables"));
+        assert!(!regex.is_match("my_variables1"));
+
+        let regex = glob("exercise0?");
+        assert!(regex.is_match("exercise05"));
+        assert!(!regex.is_match("exercise5"));
+        assert!(!regex.is_match("exercise015"));
+    }
+
+    #[test]
+    fn glob_classes_and_escaping() {
+        let regex = glob("move_semantics[1-3].rs");
+        assert!(regex.is_match("move_semantics2.rs"));
+        assert!(!regex.is_match("move_semantics4.rs"));
+        // `.` 按字面匹配
+        assert!(!regex.is_match("move_semantics2Xrs"));
+
+        let regex = glob("ex[!0]*");
+        assert!(regex.is_match("ex1"));
+        assert!(!regex.is_match("ex01"));
+
+        assert_eq!(glob_to_regex("a+b(c)"), r"^a\+b\(c\)$");
+    }
+
+    #[test]
+    fn filter_with_slash_matches_relative_path() {
+        let selection = Selection::new(
+            None,
+            Some("exercises/0[12]_*/*"),
+            false,
+            None,
+            Path::new("missing.json"),
+        )
+        .unwrap();
+        let course = selection
+            .for_course("learning-cxx", Path::new("."))
+            .unwrap();
+        assert!(course.matches("exercise01", Path::new("exercises/01_hello/main.cpp")));
+        assert!(!course.matches("exercise03", Path::new("exercises/03_loop/main.cpp")));
+    }
+
+    #[test]
+    fn only_failed_keys_by_course_and_name() {
+        let failed = [("rustlings", "intro1"), ("", "legacy")]
+            .into_iter()
+            .map(|(course, name)| (course.to_string(), name.to_string()))
+            .collect();
+        let selection = Selection {
+            failed: Some(failed),
+            ..Selection::all()
+        };
+        let rustlings = selection.for_course("rustlings", Path::new(".")).unwrap();
+        let other = selection.for_course("other", Path::new(".")).unwrap();
+        assert!(rustlings.matches("intro1", Path::new("exercises/intro1.rs")));
+        assert!(!other.matches("intro1", Path::new("exercises/intro1.rs")));
+        // 旧版评测结果没有课程，按名称匹配
+        assert!(other.matches("legacy", Path::new("legacy.rs")));
+    }
+}
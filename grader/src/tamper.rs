@@ -0,0 +1,520 @@
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::eval::ExerciseResult;
+use crate::files::course_files;
+use crate::git::{git, git_ok, lines, template_rev};
+use crate::msg;
+use crate::test_items::{code_lines, rust_test_items};
+use tracing::warn;
+
+/// 课程模板中的测试代码校验和清单，每行为 `<sha256>  <相对路径>`
+///
+/// 只从模板版本中读取，工作区中的这个文件学员可以随意改写，不作为依据。
+pub const CHECKSUMS_FILENAME: &str = "test-checksums.txt";
+
+/// 模板中受保护代码块的起止标记，写在注释中，例如 `// PROTECTED-BEGIN`
+pub const PROTECTED_BEGIN: &str = "PROTECTED-BEGIN";
+pub const PROTECTED_END: &str = "PROTECTED-END";
+
+/// 检查受保护代码块的源文件扩展名
+const SOURCE_EXTENSIONS: [&str; 8] = ["rs", "c", "cc", "cpp", "h", "hpp", "py", "go"];
+
+/// 检查 `ASSERT` 个数的 C/C++ 源文件扩展名
+const CXX_EXTENSIONS: [&str; 5] = ["c", "cc", "cpp", "h", "hpp"];
+
+/// 发现测试代码被修改时的处理方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum TamperPolicy {
+    /// 不检查
+    Off,
+    /// 只在结果中标记
+    #[default]
+    Warn,
+    /// 标记并判为未通过
+    Fail,
+}
+
+/// 判断测试代码是否被修改的依据
+enum Reference {
+    /// 模板版本中课程维护者提交的校验和清单
+    Checksums(BTreeMap<PathBuf, String>),
+    /// 课程仓库历史中的模板版本
+    Template(String),
+    /// 课程是 git 仓库但推断不出模板版本，无法检查，参数为原因
+    Unverified(String),
+    /// 没有可用的依据，不检查
+    None,
+}
+
+/// 检查练习中的测试代码是否与模板一致
+pub struct TamperCheck {
+    policy: TamperPolicy,
+    course_path: PathBuf,
+    reference: Reference,
+}
+
+/// 为课程生成测试代码校验和清单，返回清单路径和其中的文件数
+///
+/// 供课程维护者在未修改过的模板上运行，清单提交到模板仓库后才会被评测使用。
+pub fn write_checksums(course_path: &Path) -> Result<(PathBuf, usize)> {
+    let mut content = String::new();
+    let mut count = 0;
+    for relative in course_files(course_path)? {
+        let source = fs::read_to_string(course_path.join(&relative)).unwrap_or_default();
+        let Some(code) = test_fingerprint(&relative, &source) else {
+            continue;
+        };
+        content.push_str(&format!("{}  {}\n", digest(&code), slash_path(&relative)));
+        count += 1;
+    }
+
+    let path = course_path.join(CHECKSUMS_FILENAME);
+    fs::write(&path, content).context(msg!("写入 {} 失败", path.display()))?;
+    Ok((path, count))
+}
+
+impl TamperCheck {
+    /// 依据课程仓库中的模板版本：模板中有校验和清单时使用清单，否则直接与模板中的文件比较；
+    /// 课程不是 git 仓库时不检查
+    ///
+    /// 工作区中的校验和清单不作为依据。推断不出模板版本时不会退回到当前提交，
+    /// 而是在每个练习的结果中注明无法检查。
+    pub fn load(course_path: &Path, policy: TamperPolicy) -> Result<Self> {
+        let reference = if policy == TamperPolicy::Off {
+            Reference::None
+        } else {
+            match course_template_rev(course_path) {
+                Ok(Some(rev)) => match template_checksums(course_path, &rev)? {
+                    Some(checksums) => Reference::Checksums(checksums),
+                    None => Reference::Template(rev),
+                },
+                Ok(None) => Reference::None,
+                Err(e) => {
+                    warn!(error = %e, "无法检查测试代码是否被修改");
+                    Reference::Unverified(e.to_string())
+                }
+            }
+        };
+        Ok(TamperCheck {
+            policy,
+            course_path: course_path.to_path_buf(),
+            reference,
+        })
+    }
+
+    /// 检查练习文件或目录，测试代码被修改时标记结果，按策略判为未通过
+    pub fn apply(&self, mut result: ExerciseResult, exercise: &Path) -> Result<ExerciseResult> {
+        if let Reference::Unverified(reason) = &self.reference {
+            result.tamper_unverified = Some(reason.clone());
+            return Ok(result);
+        }
+        let tampered = self.tampered_files(exercise)?;
+        if tampered.is_empty() {
+            return Ok(result);
+        }
+
+        let message = msg!("测试代码与模板不一致: {}", tampered.join(", "));
+        if self.policy == TamperPolicy::Fail {
+            result.result = false;
+            result.reason = Some(match result.reason.take() {
+                Some(reason) => format!("{}\n{}", message, reason),
+                None => message,
+            });
+        }
+        result.tampered = tampered;
+        Ok(result)
+    }
+
+    /// 测试代码与依据不一致的文件
+    fn tampered_files(&self, exercise: &Path) -> Result<Vec<String>> {
+        let course_path = &self.course_path;
+        let exercise = exercise.strip_prefix(course_path).unwrap_or(exercise);
+        let mut tampered = Vec::new();
+        match &self.reference {
+            Reference::None | Reference::Unverified(_) => {}
+            Reference::Checksums(checksums) => {
+                for (relative, expected) in checksums {
+                    if !relative.starts_with(exercise) {
+                        continue;
+                    }
+                    if current_digest(relative, &course_path.join(relative)) != *expected {
+                        tampered.push(slash_path(relative));
+                    }
+                }
+            }
+            Reference::Template(rev) => {
+                let pathspec = format!("./{}", slash_path(exercise));
+                let files = lines(&git(
+                    course_path,
+                    &["ls-tree", "-r", "--name-only", rev, "--", &pathspec],
+                )?);
+                for file in files {
+                    let file = PathBuf::from(file);
+                    if !is_source(&file) {
+                        continue;
+                    }
+                    let object = format!("{}:./{}", rev, slash_path(&file));
+                    let Some(template) =
+                        test_fingerprint(&file, &git(course_path, &["show", &object])?)
+                    else {
+                        continue;
+                    };
+                    if current_digest(&file, &course_path.join(&file)) != digest(&template) {
+                        tampered.push(slash_path(&file));
+                    }
+                }
+            }
+        }
+        Ok(tampered)
+    }
+}
+
+/// 课程本身是 git 仓库（通常是子模块）时，学员开始做题时的模板版本；不是 git 仓库时为 None
+pub fn course_template_rev(course_path: &Path) -> Result<Option<String>> {
+    if course_path.join(".git").exists()
+        && git_ok(course_path, &["rev-parse", "--verify", "--quiet", "HEAD"])?
+    {
+        return Ok(Some(template_rev(course_path)?));
+    }
+    Ok(None)
+}
+
+/// 模板版本中的校验和清单，模板中没有清单时返回 None
+fn template_checksums(course_path: &Path, rev: &str) -> Result<Option<BTreeMap<PathBuf, String>>> {
+    let object = format!("{}:./{}", rev, CHECKSUMS_FILENAME);
+    if !git_ok(course_path, &["cat-file", "-e", &object])? {
+        return Ok(None);
+    }
+    let content = git(course_path, &["show", &object])?;
+    let mut checksums = BTreeMap::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let Some((hash, relative)) = line.split_once(char::is_whitespace) else {
+            bail!(msg!("{} 中的行格式不正确: {}", object, line));
+        };
+        checksums.insert(PathBuf::from(relative.trim()), hash.to_lowercase());
+    }
+    Ok(Some(checksums))
+}
+
+/// 当前文件中测试代码的校验和，文件被删除或测试代码被删光时为空串
+fn current_digest(relative: &Path, path: &Path) -> String {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|source| test_fingerprint(relative, &source))
+        .map(|code| digest(&code))
+        .unwrap_or_default()
+}
+
+fn digest(code: &str) -> String {
+    Sha256::digest(code.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn slash_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// 文件中需要与模板保持一致的部分，没有时返回 None
+///
+/// 包括受保护代码块的原文和测试的结构：Rust 文件中的每个 `#[cfg(test)]` 条目，以及每个
+/// `#[test]` 函数的名称、`#[ignore]`/`#[should_panic]` 标注和断言个数；C/C++ 文件中 `ASSERT`
+/// 的个数。断言的具体内容不比较，很多练习正是要求补全断言；注释掉的断言不算。
+fn test_fingerprint(relative: &Path, source: &str) -> Option<String> {
+    let extension = relative.extension()?.to_str()?;
+    let lines: Vec<&str> = source.lines().collect();
+    let mut parts: Vec<String> = protected_code(relative, source).into_iter().collect();
+    if extension == "rs" {
+        parts.extend(rust_test_shape(&lines));
+    } else if CXX_EXTENSIONS.contains(&extension) {
+        let assert = Regex::new(r"\bASSERT\s*\(").unwrap();
+        let count: usize = code_lines(&lines)
+            .iter()
+            .map(|line| assert.find_iter(line).count())
+            .sum();
+        if count > 0 {
+            parts.push(format!("ASSERT x{}", count));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("\n"))
+}
+
+/// Rust 测试条目的结构，每项一行，例如 `#[test] #[ignore] fn slow asserts=2`
+fn rust_test_shape(lines: &[&str]) -> Vec<String> {
+    let code = code_lines(lines);
+    let function = Regex::new(r"\bfn\s+(\w+)").unwrap();
+    let assert = Regex::new(r"\b(?:debug_)?assert(?:_eq|_ne)?!").unwrap();
+    let mut entries: Vec<(String, usize)> = Vec::new();
+    for item in rust_test_items(lines) {
+        // 函数前面尚未归属的属性
+        let mut attributes: Vec<&str> = Vec::new();
+        for line in &code[item] {
+            let line = line.trim();
+            if line.starts_with("#[cfg(test)]") {
+                entries.push(("#[cfg(test)]".to_string(), 0));
+            }
+            for attribute in ["#[test]", "#[ignore", "#[should_panic"] {
+                if line.contains(attribute) {
+                    attributes.push(attribute.trim_start_matches("#[").trim_end_matches(']'));
+                }
+            }
+            if let Some(caps) = function.captures(line) {
+                if attributes.contains(&"test") {
+                    let mut header = String::from("#[test]");
+                    for attribute in attributes.iter().filter(|a| **a != "test") {
+                        header.push_str(&format!(" #[{}]", attribute));
+                    }
+                    entries.push((format!("{} fn {}", header, &caps[1]), 0));
+                }
+                attributes.clear();
+            }
+            if let Some((_, asserts)) = entries.last_mut() {
+                *asserts += assert.find_iter(line).count();
+            }
+        }
+    }
+    entries
+        .into_iter()
+        .map(|(header, asserts)| format!("{} asserts={}", header, asserts))
+        .collect()
+}
+
+/// 提取文件中 `PROTECTED-BEGIN` 与 `PROTECTED-END` 标记之间的代码（含标记行），去掉缩进和空行
+///
+/// 标记内的代码要求逐字一致；缺少结束标记时保护到文件末尾。没有标记时返回 None。
+fn protected_code(relative: &Path, source: &str) -> Option<String> {
+    if !is_source(relative) {
+        return None;
+    }
+    let mut code: Vec<&str> = Vec::new();
+    let mut inside = false;
+    for line in source.lines().map(str::trim) {
+        if line.contains(PROTECTED_BEGIN) {
+            inside = true;
+        }
+        if inside && !line.is_empty() {
+            code.push(line);
+        }
+        if line.contains(PROTECTED_END) {
+            inside = false;
+        }
+    }
+    (!code.is_empty()).then(|| code.join("\n"))
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SOURCE_EXTENSIONS.contains(&extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protected_code_takes_only_marked_blocks() {
+        let source = "\
+fn is_even(n: i32) -> bool {
+    n % 2 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PROTECTED-BEGIN
+    #[test]
+    fn you_can_assert() {
+        assert!(is_even(10));
+    }
+    // PROTECTED-END
+
+    #[test]
+    fn learner_edits_this() {
+        assert!(todo!());
+    }
+}
+";
+        assert_eq!(
+            protected_code(Path::new("tests1.rs"), source).as_deref(),
+            Some(
+                "// PROTECTED-BEGIN\n#[test]\nfn you_can_assert() {\nassert!(is_even(10));\n}\n// PROTECTED-END"
+            )
+        );
+    }
+
+    /// rustlings 的 tests1 风格练习：学员要补全断言，但不能删掉测试
+    const RUSTLINGS: &str = "\
+fn is_even(n: i32) -> bool {
+    n % 2 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn you_can_assert() {
+        assert!(todo!());
+        assert_eq!(is_even(3), todo!());
+    }
+
+    #[test]
+    #[ignore]
+    fn later() {}
+}
+";
+
+    fn fingerprint(relative: &str, source: &str) -> Option<String> {
+        test_fingerprint(Path::new(relative), source)
+    }
+
+    #[test]
+    fn unmarked_tests_and_asserts_are_protected() {
+        assert_eq!(
+            fingerprint("tests1.rs", RUSTLINGS).as_deref(),
+            Some(
+                "#[cfg(test)] asserts=0\n#[test] fn you_can_assert asserts=2\n#[test] #[ignore] fn later asserts=0"
+            )
+        );
+
+        let cxx = "int main(int argc, char **argv) {\n    ASSERT(1 + 1 == 2, \"TODO\");\n    return 0;\n}\n";
+        assert_eq!(fingerprint("main.cpp", cxx).as_deref(), Some("ASSERT x1"));
+    }
+
+    #[test]
+    fn completing_assertions_is_not_tampering() {
+        let solved = RUSTLINGS
+            .replace("assert!(todo!());", "assert!(is_even(10));")
+            .replace(
+                "assert_eq!(is_even(3), todo!());",
+                "assert_eq!(is_even(3), false);",
+            );
+        assert_eq!(
+            fingerprint("tests1.rs", RUSTLINGS),
+            fingerprint("tests1.rs", &solved)
+        );
+
+        let cxx = "ASSERT(x == ?, \"x\");\n";
+        let solved = "ASSERT(x == 5, \"x\");\n";
+        assert_eq!(
+            fingerprint("main.cpp", cxx),
+            fingerprint("main.cpp", solved)
+        );
+    }
+
+    #[test]
+    fn removing_or_weakening_tests_is_tampering() {
+        let original = fingerprint("tests1.rs", RUSTLINGS);
+        let without_module = &RUSTLINGS[..RUSTLINGS.find("#[cfg(test)]").unwrap()];
+        let without_assert = RUSTLINGS.replace("        assert!(todo!());\n", "");
+        let commented = RUSTLINGS.replace("assert!(todo!());", "// assert!(todo!());");
+        let ignored = RUSTLINGS.replace(
+            "    #[test]\n    fn you_can_assert",
+            "    #[test]\n    #[ignore]\n    fn you_can_assert",
+        );
+        assert_eq!(fingerprint("tests1.rs", without_module), None);
+        for tampered in [without_assert, commented, ignored] {
+            assert_ne!(
+                fingerprint("tests1.rs", &tampered),
+                original,
+                "{}",
+                tampered
+            );
+        }
+
+        let cxx = "ASSERT(a, \"\");\nASSERT(b, \"\");\n";
+        for tampered in [
+            "ASSERT(a, \"\");\n",
+            "ASSERT(a, \"\");\n// ASSERT(b, \"\");\n",
+        ] {
+            assert_ne!(
+                fingerprint("main.cc", tampered),
+                fingerprint("main.cc", cxx)
+            );
+        }
+    }
+
+    fn git_in(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_AUTHOR_NAME", "learner")
+            .env("GIT_AUTHOR_EMAIL", "learner@example.com")
+            .env("GIT_COMMITTER_NAME", "learner")
+            .env("GIT_COMMITTER_EMAIL", "learner@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {}", args.join(" "));
+    }
+
+    #[test]
+    fn working_tree_checksums_are_ignored() {
+        let course = tempfile::tempdir().unwrap();
+        let path = course.path();
+        fs::create_dir(path.join("exercises")).unwrap();
+        fs::write(path.join("exercises/tests1.rs"), RUSTLINGS).unwrap();
+        git_in(path, &["init", "-q", "-b", "master"]);
+        git_in(path, &["add", "-A"]);
+        git_in(path, &["commit", "-q", "-m", "template"]);
+        git_in(
+            path,
+            &["update-ref", "refs/remotes/upstream/master", "HEAD"],
+        );
+
+        // 删掉测试后重新生成校验和清单，也不能通过检查
+        let solved = &RUSTLINGS[..RUSTLINGS.find("#[cfg(test)]").unwrap()];
+        fs::write(path.join("exercises/tests1.rs"), solved).unwrap();
+        write_checksums(path).unwrap();
+
+        let check = TamperCheck::load(path, TamperPolicy::Fail).unwrap();
+        let result = ExerciseResult {
+            name: "tests1".to_string(),
+            result: true,
+            ..Default::default()
+        };
+        let result = check
+            .apply(result, &path.join("exercises/tests1.rs"))
+            .unwrap();
+        assert_eq!(result.tampered, ["exercises/tests1.rs"]);
+        assert!(!result.result);
+    }
+
+    #[test]
+    fn unterminated_block_runs_to_end_of_file() {
+        let source = "int x;\n// PROTECTED-BEGIN\nASSERT(x == 0, \"\");\n\nreturn 0;\n";
+        assert_eq!(
+            protected_code(Path::new("main.cc"), source).as_deref(),
+            Some("// PROTECTED-BEGIN\nASSERT(x == 0, \"\");\nreturn 0;")
+        );
+    }
+
+    #[test]
+    fn indentation_changes_are_not_tampering() {
+        let template = "// PROTECTED-BEGIN\nassert!(f());\n// PROTECTED-END\n";
+        let reformatted = "    // PROTECTED-BEGIN\n        assert!(f());\n\n    // PROTECTED-END\n";
+        assert_eq!(
+            protected_code(Path::new("a.rs"), template),
+            protected_code(Path::new("a.rs"), reformatted)
+        );
+    }
+
+    #[test]
+    fn other_files_are_ignored() {
+        let source = "// PROTECTED-BEGIN\nsecret\n// PROTECTED-END\n";
+        assert_eq!(protected_code(Path::new("README.md"), source), None);
+        assert!(protected_code(Path::new("main.py"), source).is_some());
+    }
+}
@@ -0,0 +1,305 @@
+use std::ops::RangeInclusive;
+
+/// 扫描到行尾时所处的位置，块注释和字符串可以跨行
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Code,
+    /// 块注释，参数为嵌套层数
+    BlockComment(usize),
+    Str,
+    /// 原始字符串，参数为 `#` 的个数
+    RawStr(usize),
+}
+
+/// 一行中字符串、字符字面量和注释以外的花括号和分号
+struct Line {
+    /// 行首是否处在代码中，而不是注释或字符串中
+    starts_in_code: bool,
+    marks: Vec<char>,
+    /// 去掉注释、字符串内容和字符字面量后的代码，字符串只保留引号
+    code: String,
+}
+
+/// Rust 源码中 `#[cfg(test)]` 和 `#[test]` 标注的条目所占的行
+///
+/// 字符串、字符字面量和注释中的花括号不参与配平，注释和字符串中的属性也不算。
+pub fn rust_test_items(lines: &[&str]) -> Vec<RangeInclusive<usize>> {
+    let scanned = scan(lines);
+    let mut items = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index].trim_start();
+        if scanned[index].starts_in_code
+            && (line.starts_with("#[cfg(test)]") || line.starts_with("#[test]"))
+        {
+            let end = item_end(&scanned, index);
+            items.push(index..=end);
+            index = end + 1;
+        } else {
+            index += 1;
+        }
+    }
+    items
+}
+
+/// 去掉注释、字符串内容和字符字面量后的每一行，字符串只保留一对引号
+///
+/// C/C++ 的注释和字符串与 Rust 写法相同，也可以用来处理 C/C++ 源码。
+pub fn code_lines(lines: &[&str]) -> Vec<String> {
+    scan(lines).into_iter().map(|line| line.code).collect()
+}
+
+/// 从属性所在行开始，找到条目结束的行：花括号配平或遇到不带花括号的 `;`
+fn item_end(scanned: &[Line], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut opened = false;
+    for (index, line) in scanned.iter().enumerate().skip(start) {
+        for &mark in &line.marks {
+            match mark {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    if opened && depth == 0 {
+                        return index;
+                    }
+                }
+                ';' if !opened => return index,
+                _ => {}
+            }
+        }
+    }
+    scanned.len() - 1
+}
+
+fn scan(lines: &[&str]) -> Vec<Line> {
+    let mut state = State::Code;
+    let mut scanned = Vec::with_capacity(lines.len());
+    for line in lines {
+        let starts_in_code = state == State::Code;
+        let mut marks = Vec::new();
+        let mut code = String::new();
+        let chars: Vec<char> = line.chars().collect();
+        let at = |i: usize| chars.get(i).copied();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match state {
+                State::Code => match c {
+                    '/' if at(i + 1) == Some('/') => break,
+                    '/' if at(i + 1) == Some('*') => {
+                        state = State::BlockComment(1);
+                        i += 1;
+                    }
+                    '"' => {
+                        code.push('"');
+                        state = State::Str;
+                    }
+                    'r' if raw_string_start(&chars, i) => {
+                        let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+                        code.push('"');
+                        state = State::RawStr(hashes);
+                        i += hashes + 1;
+                    }
+                    // 字符字面量，否则是生命周期
+                    '\'' if at(i + 1) == Some('\\') => {
+                        i += 3;
+                        while i < chars.len() && chars[i] != '\'' {
+                            i += 1;
+                        }
+                    }
+                    '\'' if at(i + 2) == Some('\'') => i += 2,
+                    '{' | '}' | ';' => {
+                        marks.push(c);
+                        code.push(c);
+                    }
+                    _ => code.push(c),
+                },
+                State::BlockComment(depth) => {
+                    if c == '/' && at(i + 1) == Some('*') {
+                        state = State::BlockComment(depth + 1);
+                        i += 1;
+                    } else if c == '*' && at(i + 1) == Some('/') {
+                        state = if depth == 1 {
+                            State::Code
+                        } else {
+                            State::BlockComment(depth - 1)
+                        };
+                        i += 1;
+                    }
+                }
+                State::Str => match c {
+                    '\\' => i += 1,
+                    '"' => {
+                        code.push('"');
+                        state = State::Code;
+                    }
+                    _ => {}
+                },
+                State::RawStr(hashes) => {
+                    if c == '"' && (1..=hashes).all(|k| at(i + k) == Some('#')) {
+                        code.push('"');
+                        state = State::Code;
+                        i += hashes;
+                    }
+                }
+            }
+            i += 1;
+        }
+        scanned.push(Line {
+            starts_in_code,
+            marks,
+            code,
+        });
+    }
+    scanned
+}
+
+/// `r"`、`r#"`、`br"` 等原始字符串的开头，而不是以 r 结尾的标识符
+fn raw_string_start(chars: &[char], i: usize) -> bool {
+    let ident = |c: char| c.is_alphanumeric() || c == '_';
+    let prefix_ok = match i.checked_sub(1).map(|j| chars[j]) {
+        None => true,
+        Some('b') => i < 2 || !ident(chars[i - 2]),
+        Some(c) => !ident(c),
+    };
+    let rest = chars[i + 1..].iter().find(|&&c| c != '#');
+    prefix_ok && rest == Some(&'"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(source: &str) -> Vec<RangeInclusive<usize>> {
+        let lines: Vec<&str> = source.lines().collect();
+        rust_test_items(&lines)
+    }
+
+    #[test]
+    fn finds_test_module_and_attribute_items() {
+        let source = "\
+fn main() {}
+
+#[cfg(test)]
+use std::fmt;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn t() {}
+}
+";
+        assert_eq!(items(source), vec![2..=3, 5..=9]);
+    }
+
+    #[test]
+    fn braces_in_strings_do_not_end_the_item() {
+        let source = "\
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn closing() {
+        assert_eq!(format!(\"{}\", 1), \"}\");
+        assert_eq!(\"\\\"}\", r#\"\"}\"#);
+    }
+}
+fn learner_code() {}
+";
+        assert_eq!(items(source), vec![0..=7]);
+    }
+
+    #[test]
+    fn braces_in_char_literals_do_not_run_on() {
+        let source = "\
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn chars() {
+        assert_eq!(open(), '{');
+        assert_eq!(quote(), '\\'');
+        assert_eq!(brace(), '\\u{7b}');
+    }
+}
+fn open() -> char { '{' }
+";
+        assert_eq!(items(source), vec![0..=8]);
+    }
+
+    #[test]
+    fn lifetimes_are_not_char_literals() {
+        let source = "\
+#[cfg(test)]
+mod tests {
+    fn first<'a>(s: &'a str) -> &'a str { s }
+}
+fn after() {}
+";
+        assert_eq!(items(source), vec![0..=3]);
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let source = "\
+#[cfg(test)]
+mod tests {
+    // } 不是结束
+    /* { /* 嵌套 } */
+       #[test] */
+    #[test]
+    fn t() {}
+}
+/*
+#[test]
+fn commented_out() {}
+*/
+fn after() {}
+";
+        assert_eq!(items(source), vec![0..=7]);
+    }
+
+    #[test]
+    fn multiline_strings_are_skipped() {
+        let source = "\
+#[test]
+fn t() {
+    let s = \"
+}
+#[test]
+\";
+}
+fn after() {}
+";
+        assert_eq!(items(source), vec![0..=6]);
+    }
+
+    #[test]
+    fn code_lines_drop_comments_and_string_contents() {
+        let source = "\
+assert!(a); // assert!(b);
+/* assert!(c);
+   assert!(d); */ assert_eq!(s, \"assert!(e)\");
+let c = '}';
+";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            code_lines(&lines),
+            ["assert!(a); ", "", " assert_eq!(s, \"\");", "let c = ;"]
+        );
+    }
+
+    #[test]
+    fn identifiers_ending_in_r_are_not_raw_strings() {
+        let source = "\
+#[test]
+fn t() {
+    let bar = 1;
+    assert_eq!(format!(\"{bar}\"), br\"1\".len().to_string());
+}
+fn after() {}
+";
+        assert_eq!(items(source), vec![0..=4]);
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::msg;
+use crate::test_items::rust_test_items;
+
+/// 旧版 rustlings 练习中表示“尚未完成”的标记，学员完成后删除
+const NOT_DONE_MARKER: &str = "// I AM NOT DONE";
+
+/// 练习代码中的占位宏，学员需要替换为自己的实现
+const PLACEHOLDERS: [&str; 2] = ["todo!(", "unimplemented!("];
+
+/// 判断 rustlings 练习是否尚未完成的依据
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum UnfinishedCheck {
+    /// 不检查
+    Off,
+    /// 只检查 `// I AM NOT DONE` 标记
+    Marker,
+    /// 检查标记以及测试以外代码中的 `todo!()` 和 `unimplemented!()`
+    #[default]
+    All,
+}
+
+impl UnfinishedCheck {
+    /// 练习尚未完成时返回原因，例如 “第 3 行有 // I AM NOT DONE 标记”
+    pub fn find(self, source: &str) -> Option<String> {
+        if self == UnfinishedCheck::Off {
+            return None;
+        }
+
+        let lines: Vec<&str> = source.lines().collect();
+        if let Some(index) = lines.iter().position(|line| line.trim() == NOT_DONE_MARKER) {
+            return Some(msg!("第 {} 行有 {} 标记", index + 1, NOT_DONE_MARKER));
+        }
+        if self == UnfinishedCheck::Marker {
+            return None;
+        }
+
+        // 测试中的占位宏不需要学员实现
+        let tests = rust_test_items(&lines);
+        for (index, line) in lines.iter().enumerate() {
+            if tests.iter().any(|item| item.contains(&index)) {
+                continue;
+            }
+            let code = line.split("//").next().unwrap_or_default();
+            if let Some(placeholder) = PLACEHOLDERS.iter().find(|p| code.contains(*p)) {
+                return Some(msg!("第 {} 行有占位的 {})", index + 1, placeholder));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// rustlings 5.x 的 variables1.rs
+    const MARKED: &str = "// variables1.rs
+//
+// Make me compile!
+//
+// Execute `rustlings hint variables1` or use the `hint` watch subcommand for a
+// hint.
+
+// I AM NOT DONE
+
+fn main() {
+    x = 5;
+    println!(\"x has the value {}\", x);
+}
+";
+
+    /// 仿照 rustlings 6.x 的练习写法，测试里的 `todo!()` 不需要学员实现
+    const PLACEHOLDER: &str = "fn bigger(a: i32, b: i32) -> i32 {
+    // TODO: Complete this function to return the bigger number!
+    // Don't use `todo!()` in your solution.
+    todo!()
+}
+
+fn main() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ten_is_bigger_than_eight() {
+        assert_eq!(10, bigger(10, 8));
+    }
+
+    #[test]
+    #[ignore]
+    fn later() {
+        todo!()
+    }
+}
+";
+
+    #[test]
+    fn marker_is_reported_with_its_line() {
+        let reason = Some("第 8 行有 // I AM NOT DONE 标记".to_string());
+        assert_eq!(UnfinishedCheck::All.find(MARKED), reason);
+        assert_eq!(UnfinishedCheck::Marker.find(MARKED), reason);
+        // 缩进的标记也算
+        assert!(
+            UnfinishedCheck::Marker
+                .find("fn main() {\n    // I AM NOT DONE\n}\n")
+                .is_some()
+        );
+        assert_eq!(UnfinishedCheck::Off.find(MARKED), None);
+    }
+
+    #[test]
+    fn placeholders_outside_tests() {
+        assert_eq!(
+            UnfinishedCheck::All.find(PLACEHOLDER),
+            Some("第 4 行有占位的 todo!()".to_string())
+        );
+        assert_eq!(UnfinishedCheck::Marker.find(PLACEHOLDER), None);
+        assert_eq!(
+            UnfinishedCheck::All.find("fn f() {\n    unimplemented!(\"later\")\n}\n"),
+            Some("第 2 行有占位的 unimplemented!()".to_string())
+        );
+    }
+
+    #[test]
+    fn placeholders_in_tests_and_comments_are_ignored() {
+        let finished =
+            PLACEHOLDER.replace("    todo!()\n}\n\nfn main", "    a.max(b)\n}\n\nfn main");
+        assert_eq!(UnfinishedCheck::All.find(&finished), None);
+        assert_eq!(
+            UnfinishedCheck::All.find("fn f() -> i32 {\n    1 // not todo!() anymore\n}\n"),
+            None
+        );
+    }
+}
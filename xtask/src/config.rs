@@ -0,0 +1,383 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use colored::*;
+use grader::i18n::Lang;
+use grader::tamper::TamperPolicy;
+use grader::unfinished::UnfinishedCheck;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::eval::RESULT_FILENAME;
+use crate::i18n::localize_text;
+use crate::t;
+
+/// 工作空间根目录下的配置文件
+pub const CONFIG_FILENAME: &str = "xtask.toml";
+
+/// xtask 的配置，命令行参数优先于配置
+///
+/// 依次读取工作空间的 xtask.toml 和用户配置目录下的 learning-tools/xtask.toml，
+/// 后者中的设置覆盖前者；都没有时使用默认值。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 命令行输出的语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    pub eval: EvalConfig,
+    pub sandbox: SandboxConfig,
+    pub submit: SubmitConfig,
+    /// 课程目录名到提交平台课程 ID 的映射，提交评测结果时使用
+    pub courses: BTreeMap<String, String>,
+}
+
+/// 评测的默认设置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct EvalConfig {
+    /// 未指定 --course 时评测的课程，为空时评测所有课程
+    pub courses: Vec<String>,
+    /// 练习目录路径
+    pub path: PathBuf,
+    /// 评测结果文件
+    pub result: PathBuf,
+    /// cargo 构建的并行数，不设置时由 cargo 决定
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    /// 每个构建、测试或运行命令的时间上限（秒），不设置时不限制
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// 测试代码与模板不一致时的处理方式
+    pub tamper: TamperPolicy,
+    /// 判断 rustlings 练习尚未完成的依据
+    pub unfinished: UnfinishedCheck,
+}
+
+/// 沙箱的默认设置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SandboxConfig {
+    /// 是否默认在沙箱中运行学员代码
+    pub enabled: bool,
+    /// 每个进程的内存上限（MiB）
+    pub memory: u64,
+    /// 每个进程的 CPU 时间上限（秒）
+    pub cpu: u64,
+    /// 进程数上限，只在 bubblewrap 沙箱中生效
+    pub processes: u64,
+}
+
+/// `cargo xtask submit` 提交评测结果的设置
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubmitConfig {
+    /// 接收评测结果的地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+impl Default for EvalConfig {
+    fn default() -> Self {
+        EvalConfig {
+            courses: Vec::new(),
+            path: PathBuf::from("."),
+            result: PathBuf::from(RESULT_FILENAME),
+            jobs: None,
+            timeout: None,
+            tamper: TamperPolicy::Warn,
+            unfinished: UnfinishedCheck::All,
+        }
+    }
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        SandboxConfig {
+            enabled: false,
+            memory: 4096,
+            cpu: 120,
+            processes: 1024,
+        }
+    }
+}
+
+impl Config {
+    /// 读取并合并工作空间和用户的配置
+    pub fn load() -> Result<Self> {
+        Ok(Self::load_files()?.0)
+    }
+
+    /// 读取配置，同时返回实际读取的文件
+    fn load_files() -> Result<(Self, Vec<PathBuf>)> {
+        Self::load_paths(&config_paths(|name| std::env::var_os(name)))
+    }
+
+    /// 依次读取并合并存在的配置文件，后面的覆盖前面的
+    fn load_paths(paths: &[PathBuf]) -> Result<(Self, Vec<PathBuf>)> {
+        let mut merged = toml::Table::new();
+        let mut loaded = Vec::new();
+        for path in paths {
+            if !path.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(path).context(t!("读取 {} 失败", path.display()))?;
+            let table: toml::Table =
+                toml::from_str(&content).context(t!("解析 {} 失败", path.display()))?;
+            merge(&mut merged, table);
+            loaded.push(path.clone());
+        }
+        let config =
+            toml::Value::Table(merged)
+                .try_into()
+                .with_context(|| match loaded.as_slice() {
+                    [] => t!("配置无效").to_string(),
+                    files => t!(
+                        "配置无效: {}",
+                        files
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                })?;
+        Ok((config, loaded))
+    }
+}
+
+/// 配置文件的读取顺序，后读取的覆盖先读取的；`var` 读取环境变量
+fn config_paths(var: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+    let user_dir = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| var("APPDATA").map(PathBuf::from));
+    std::iter::once(PathBuf::from(CONFIG_FILENAME))
+        .chain(user_dir.map(|dir| dir.join("learning-tools").join(CONFIG_FILENAME)))
+        .collect()
+}
+
+/// 把 overlay 中的设置合并进 base，同名的表逐项合并，其余值直接覆盖
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[clap(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// 显示合并后实际生效的配置
+    Show {
+        /// 只显示一项配置，例如 submit.endpoint 或 courses.rustlings，便于在脚本中读取
+        key: Option<String>,
+    },
+}
+
+impl ConfigArgs {
+    pub fn config(self) {
+        if let Err(e) = self.run_config() {
+            eprintln!(
+                "{} {}",
+                t!("读取配置失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_config(&self) -> Result<()> {
+        match &self.command {
+            ConfigCommand::Show { key: Some(key) } => {
+                let config =
+                    toml::Value::try_from(Config::load()?).context(t!("序列化配置失败"))?;
+                let value = key
+                    .split('.')
+                    .try_fold(&config, |value, part| value.get(part))
+                    .with_context(|| t!("没有配置项 {}", key))?;
+                match value {
+                    toml::Value::String(value) => println!("{}", value),
+                    toml::Value::Table(table) => print!(
+                        "{}",
+                        toml::to_string_pretty(table).context(t!("序列化配置失败"))?
+                    ),
+                    value => println!("{}", value),
+                }
+            }
+            ConfigCommand::Show { key: None } => {
+                let (config, loaded) = Config::load_files()?;
+                if loaded.is_empty() {
+                    println!("{}", t!("# 没有找到配置文件，使用默认配置").blue());
+                } else {
+                    for path in &loaded {
+                        println!("{} {}", t!("# 已读取:").blue(), path.display());
+                    }
+                }
+                print!(
+                    "{}",
+                    toml::to_string_pretty(&config).context(t!("序列化配置失败"))?
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::eval::GradeOptions;
+
+    fn table(content: &str) -> toml::Table {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn merge_overrides_values_and_merges_nested_tables() {
+        let mut base = table(
+            r#"
+            lang = "zh-CN"
+            [eval]
+            jobs = 4
+            courses = ["rustlings", "learning-cxx"]
+            [sandbox]
+            memory = 1024
+            "#,
+        );
+        merge(
+            &mut base,
+            table(
+                r#"
+                lang = "en"
+                [eval]
+                courses = ["rustlings"]
+                timeout = 60
+                [courses]
+                rustlings = "1885"
+                "#,
+            ),
+        );
+        assert_eq!(
+            base,
+            table(
+                r#"
+                lang = "en"
+                [eval]
+                jobs = 4
+                courses = ["rustlings"]
+                timeout = 60
+                [sandbox]
+                memory = 1024
+                [courses]
+                rustlings = "1885"
+                "#,
+            )
+        );
+
+        // 数组和其他值整个替换，值也可以替换表
+        merge(&mut base, table("sandbox = 1"));
+        assert_eq!(base["sandbox"], toml::Value::Integer(1));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        for content in [
+            "[eval]\njob = 4\n",
+            "colour = true\n",
+            "[submit]\nurl = \"x\"\n",
+        ] {
+            let path = dir.path().join(CONFIG_FILENAME);
+            fs::write(&path, content).unwrap();
+            let error = Config::load_paths(std::slice::from_ref(&path)).unwrap_err();
+            assert_eq!(error.to_string(), format!("配置无效: {}", path.display()));
+        }
+    }
+
+    #[test]
+    fn user_config_overrides_workspace_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace.toml");
+        let user = dir.path().join("user.toml");
+        let missing = dir.path().join("missing.toml");
+        fs::write(
+            &workspace,
+            "[eval]\njobs = 4\ntimeout = 60\n[courses]\nrustlings = \"1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &user,
+            "[eval]\njobs = 8\n[submit]\nendpoint = \"https://example.com\"\n",
+        )
+        .unwrap();
+
+        let (config, loaded) =
+            Config::load_paths(&[workspace.clone(), missing, user.clone()]).unwrap();
+        assert_eq!(loaded, [workspace, user]);
+        assert_eq!(config.eval.jobs, Some(8));
+        assert_eq!(config.eval.timeout, Some(60));
+        assert_eq!(config.courses["rustlings"], "1");
+        assert_eq!(
+            config.submit.endpoint.as_deref(),
+            Some("https://example.com")
+        );
+        // 没有设置的项取默认值
+        assert_eq!(config.sandbox.memory, 4096);
+    }
+
+    #[test]
+    fn user_config_is_read_from_xdg_config_home() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        let user = |dir: &str| Path::new(dir).join("learning-tools").join(CONFIG_FILENAME);
+
+        assert_eq!(
+            config_paths(env(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/home/a")])),
+            [PathBuf::from(CONFIG_FILENAME), user("/xdg")]
+        );
+        assert_eq!(
+            config_paths(env(&[("HOME", "/home/a")])),
+            [PathBuf::from(CONFIG_FILENAME), user("/home/a/.config")]
+        );
+        assert_eq!(config_paths(env(&[])), [PathBuf::from(CONFIG_FILENAME)]);
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        #[derive(Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            options: GradeOptions,
+        }
+        let mut config = Config::default();
+        config.eval.jobs = Some(8);
+        config.eval.tamper = TamperPolicy::Fail;
+        let root = Path::new("/workspace");
+
+        let grader = Cli::parse_from(["xtask"]).options.grader(&config, root).0;
+        assert_eq!(grader.sandbox.jobs, Some(8));
+        assert_eq!(grader.tamper, TamperPolicy::Fail);
+
+        let cli = Cli::parse_from(["xtask", "--jobs", "2", "--tamper", "off"]);
+        let grader = cli.options.grader(&config, root).0;
+        assert_eq!(grader.sandbox.jobs, Some(2));
+        assert_eq!(grader.tamper, TamperPolicy::Off);
+    }
+}
@@ -1,554 +1,376 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use serde::{Deserialize, Serialize};
+use grader::discover::{CourseDir, course_dir, course_dirs, exercises_dir};
+use grader::eval::{CxxBackend, CxxOptions, ExerciseResult, GradeResult, Grader, merge_results};
+use grader::gitmodules::Submodule;
+use grader::libtest::TestStatus;
+use grader::logs::RunLog;
+use grader::selection::Selection;
+use grader::tamper::TamperPolicy;
+use grader::unfinished::UnfinishedCheck;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
 use std::time::Instant;
-use tempfile;
+use tracing::debug_span;
+
+use crate::config::Config;
+use crate::i18n::{self, localize_text, localized};
+use crate::report::Terminal;
+use crate::sandbox::{self, SandboxOptions};
+use crate::t;
+use crate::{progress, trace};
+
+/// 默认的评测结果文件，写在当前目录下
+pub const RESULT_FILENAME: &str = "eval_result.json";
 
 #[derive(Args)]
 pub struct EvalArgs {
-    /// 要评分的课程名称，不传则自动对所有已配置课程评分
+    /// 要评分的课程名称，不传则评测 xtask.toml 中配置的课程，没有配置时对所有已配置课程评分
     #[clap(long)]
     course: Option<String>,
-    
-    /// 练习目录路径，默认为当前目录
-    #[clap(short, long, default_value = ".")]
-    path: PathBuf,
-    
-    /// 是否显示详细输出
+
+    /// 练习目录路径，默认取 xtask.toml 中的设置，否则为当前目录
     #[clap(short, long)]
+    path: Option<PathBuf>,
+
+    /// 评测结果文件，默认取 xtask.toml 中的设置，否则为 eval_result.json
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// 是否显示详细输出，由全局的 -v 决定
+    #[clap(skip)]
     verbose: bool,
-}
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ExerciseResult {
-    pub name: String,
-    pub result: bool,
-}
+    /// 只评测指定的练习，例如 variables3、variables3.rs 或 exercise05
+    #[clap(long)]
+    exercise: Option<String>,
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Statistics {
-    pub total_exercations: usize,
-    pub total_succeeds: usize,
-    pub total_failures: usize,
-    pub total_time: u64,
-}
+    /// 按名称筛选练习，包含 / 时按相对路径筛选；默认为 glob（如 functions*），以 re: 开头时为正则表达式
+    #[clap(long)]
+    filter: Option<String>,
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GradeResult {
-    pub exercises: Vec<ExerciseResult>,
-    pub statistics: Statistics,
+    /// 只评测上一次评测结果中未通过的练习
+    #[clap(long)]
+    only_failed: bool,
+
+    /// 只评测自指定提交以来改动过的练习
+    #[clap(long)]
+    since: Option<String>,
+
+    #[clap(flatten)]
+    options: GradeOptions,
+
+    /// xtask.toml 中的配置，命令行参数未指定的选项取这里的设置
+    #[clap(skip)]
+    config: Box<Config>,
 }
 
-impl EvalArgs {
-    pub fn eval(self) {
-        if let Err(e) = self.run_eval() {
-            eprintln!("{} {}", "评分失败:".red().bold(), e);
-        }
-    }
-    
-    /// 评测learning-lm-rs项目
-    fn eval_learning_lm(&self, lm_path: &Path) -> Result<(Vec<ExerciseResult>, usize, usize, usize)> {
-        println!("{}", "评测 learning-lm-rs 项目...".blue().bold());
-        
-        let manifest_path = lm_path.join("Cargo.toml");
-        if !manifest_path.exists() {
-            println!("{} {} {}", "警告:".yellow().bold(), "找不到 learning-lm-rs/Cargo.toml 文件:", manifest_path.display());
-            return Ok((Vec::new(), 0, 0, 0));
-        }
+/// 评测练习的选项，eval 和 watch 共用，未指定的选项取 xtask.toml 中的设置
+#[derive(Args, Clone, Debug)]
+pub struct GradeOptions {
+    #[clap(flatten)]
+    cxx: CxxArgs,
 
-        println!("{} {}", "运行测试:".blue().bold(), "cargo test --release");
-        let test_output = Command::new("cargo")
-            .arg("test")
-            .arg("--manifest-path")
-            .arg(&manifest_path)
-            .arg("--release")
-            .current_dir(lm_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .context("运行 learning-lm-rs 测试失败")?;
-
-        let success = test_output.status.success();
-
-        if self.verbose || !success {
-            println!("{}", String::from_utf8_lossy(&test_output.stdout));
-            println!("{}", String::from_utf8_lossy(&test_output.stderr));
-        }
+    #[clap(flatten)]
+    sandbox: SandboxOptions,
 
-        // learning-lm-rs 只包含 model.rs 和 operators.rs
-        let lm_exercises = ["model.rs", "operators.rs"];
-        let total_exercations = lm_exercises.len();
-        let mut exercise_results = Vec::new();
-        let mut total_succeeds = 0;
-        let mut total_failures = 0;
-
-        for &exercise_name in lm_exercises.iter() {
-            exercise_results.push(ExerciseResult {
-                name: exercise_name.to_string(),
-                result: success,
-            });
-            if success {
-                total_succeeds += 1;
-                println!("{} {}", "✓".green().bold(), exercise_name);
-            } else {
-                total_failures += 1;
-                println!("{} {}", "✗".red().bold(), exercise_name);
-            }
-        }
-        println!("评测完成!");
-        
-        Ok((exercise_results, total_succeeds, total_failures, total_exercations))
-    }
-    
-    /// 评测learning-cxx项目
-    fn eval_learning_cxx(&self, course_path: &Path) -> Result<(Vec<ExerciseResult>, usize, usize, usize)> {
-        println!("{}", "评测 learning-cxx 项目...".blue().bold());
-        
-        // 运行xmake run summary命令获取评测结果
-        let output = Command::new("xmake")
-            .arg("run")
-            .arg("summary")
-            .current_dir(course_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .context("运行 xmake run summary 失败")?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let error_str = String::from_utf8_lossy(&output.stderr);
-        
-        if self.verbose {
-            println!("{}", output_str);
-            println!("{}", error_str);
-        }
+    /// 测试代码与模板不一致时的处理方式，依据为课程仓库中的模板版本，默认为 warn
+    #[clap(long, value_parser = localized::<TamperPolicy>())]
+    tamper: Option<TamperPolicy>,
 
-        let mut exercise_results = Vec::new();
-        let mut total_succeeds = 0;
-        let mut total_failures = 0;
-        let mut total_exercations = 0;
+    /// 用标准测试替换 rustlings 练习中的测试后再评测，标准测试来自课程的 reference-tests 目录或课程仓库中的模板版本
+    #[clap(long)]
+    reference_tests: bool,
 
-        // 移除ANSI转义序列的正则表达式
-        let re = regex::Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]").unwrap();
+    /// 判断 rustlings 练习尚未完成的依据，尚未完成的练习不评测、判为未通过，默认为 all
+    #[clap(long, value_parser = localized::<UnfinishedCheck>())]
+    unfinished: Option<UnfinishedCheck>,
+}
 
-        // 解析输出结果
-        for line in output_str.lines() {
-            // 跳过错误信息行
-            if line.contains("error:") {
-                continue;
-            }
+impl GradeOptions {
+    /// 合并命令行和配置中的评测选项，同时返回沙箱的警告；评测日志由调用方按需设置
+    pub fn grader(&self, config: &Config, workspace_root: &Path) -> (Grader, Vec<String>) {
+        let (sandbox, warnings) = self.sandbox.sandbox(config);
+        let grader = Grader {
+            sandbox,
+            cxx: self.cxx.options(),
+            tamper: self.tamper.unwrap_or(config.eval.tamper),
+            unfinished: self.unfinished.unwrap_or(config.eval.unfinished),
+            reference_tests: self.reference_tests,
+            workspace_root: workspace_root.to_path_buf(),
+            log: None,
+            lang: i18n::lang(),
+        };
+        (grader, warnings)
+    }
+}
 
-            if line.contains("exercise") && (line.contains("passed") || line.contains("failed")) {
-                total_exercations += 1;
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(exercise_name) = parts.first() {
-                    // 移除ANSI转义序列
-                    let clean_name = re.replace_all(exercise_name, "").to_string();
-                    let result = line.contains("passed");
-                    if result {
-                        total_succeeds += 1;
-                        println!("{} {}", "✓".green().bold(), clean_name);
-                    } else {
-                        total_failures += 1;
-                        println!("{} {}", "✗".red().bold(), clean_name);
-                    }
-                    exercise_results.push(ExerciseResult { name: clean_name, result });
-                }
-            }
-        }
+/// C++ 练习的评测选项
+#[derive(Args, Clone, Debug)]
+pub struct CxxArgs {
+    /// learning-cxx 的评测方式
+    #[clap(long, value_parser = localized::<CxxBackend>(), default_value = "xmake")]
+    pub cxx_backend: CxxBackend,
 
-        // 如果没有从stdout找到结果，尝试从stderr中解析
-        if total_exercations == 0 {
-            for line in error_str.lines() {
-                // 跳过错误信息行
-                if line.contains("error:") {
-                    continue;
-                }
+    /// direct 方式使用的 C++ 标准，额外的编译选项可以通过 CXXFLAGS 环境变量传入
+    #[clap(long, default_value = "c++17")]
+    pub cxx_std: String,
+}
 
-                if line.contains("exercise") && (line.contains("passed") || line.contains("failed")) {
-                    total_exercations += 1;
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if let Some(exercise_name) = parts.first() {
-                        // 移除ANSI转义序列并处理exercise前缀
-                        let clean_name = re.replace_all(exercise_name, "").to_string();
-                        let name = clean_name.trim_start_matches("exercise").to_string();
-                        let result = line.contains("passed");
-                        if result {
-                            total_succeeds += 1;
-                            println!("{} exercise{}", "✓".green().bold(), name);
-                        } else {
-                            total_failures += 1;
-                            println!("{} exercise{}", "✗".red().bold(), name);
-                        }
-                        exercise_results.push(ExerciseResult { name: format!("exercise{}", name), result });
-                    }
-                }
-            }
+impl CxxArgs {
+    pub fn options(&self) -> CxxOptions {
+        CxxOptions {
+            backend: self.cxx_backend,
+            std: self.cxx_std.clone(),
         }
-
-        println!("评测完成!");
-        Ok((exercise_results, total_succeeds, total_failures, total_exercations))
     }
+}
 
-    /// 评测rustlings或其他项目
-    fn eval_rustlings(&self, course_path: &Path) -> Result<(Vec<ExerciseResult>, usize, usize, usize)> {
-        println!("{}", "评测 rustlings 项目...".blue().bold());
-        
-        // 使用 rustc 编译和运行测试来评测
-        println!("{}", "使用 rustc 编译和运行测试来评测...".blue().bold());
-        
-        // 处理 Rustlings 或其他非 learning-lm-rs 项目
-        let exercise_files = find_exercise_files(course_path, &None)?;
-        let total_exercations = exercise_files.len();
-        println!("{} {} {}", "找到".blue().bold(), total_exercations, "个练习文件".blue().bold());
-
-        if total_exercations == 0 {
-            println!("{}", "未找到练习文件，评测结束。".yellow());
-            return Ok((Vec::new(), 0, 0, 0));
+impl EvalArgs {
+    pub fn eval(mut self) {
+        self.verbose = trace::verbose();
+        let result = Config::load().and_then(|config| {
+            *self.config = config;
+            self.run_eval()
+        });
+        if let Err(e) = result {
+            eprintln!(
+                "{} {}",
+                t!("评分失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
         }
+    }
 
-        let bar = ProgressBar::new(total_exercations as u64);
-        bar.set_style(
-            ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                .unwrap()
-                .progress_chars("##-"),
-        );
+    fn result_path(&self) -> &Path {
+        self.output.as_deref().unwrap_or(&self.config.eval.result)
+    }
 
-        let mut exercise_results = Vec::new();
-        let mut total_succeeds = 0;
-        let mut total_failures = 0;
-        
-        for exercise_path in exercise_files.iter() {
-            bar.inc(1);
-            let (name, result, _time) = grade_exercise(exercise_path, self.verbose)?;
-            if result {
-                total_succeeds += 1;
-            } else {
-                total_failures += 1;
-            }
-            exercise_results.push(ExerciseResult { name, result });
+    fn grade_course(
+        &self,
+        grader: &Grader,
+        course: &CourseDir,
+        selection: &Selection,
+        reporter: &Terminal,
+    ) -> Result<Vec<ExerciseResult>> {
+        let course_selection = selection.for_course(&course.name, &course.path)?;
+        let results = grader.grade_course(&course.name, &course.path, &course_selection, reporter);
+        reporter.finish();
+        let results = results?;
+        if results.is_empty() {
+            progress!("{}", t!("没有符合筛选条件的练习").yellow());
         }
-        bar.finish_with_message("评测完成!");
-        
-        Ok((exercise_results, total_succeeds, total_failures, total_exercations))
+        progress!("{}", t!("评测完成!"));
+        Ok(results)
     }
-    
+
     fn run_eval(&self) -> Result<()> {
-        println!("{}", "开始评测练习...".blue().bold());
+        progress!("{}", t!("开始评测练习...").blue().bold());
         let start_time = Instant::now();
 
-        // 获取当前工作目录
-        let current_dir = std::env::current_dir().context("无法获取当前工作目录")?;
-        
+        // 筛选条件要在覆盖上一次的评测结果之前读取
+        let selection = Selection::new(
+            self.exercise.clone(),
+            self.filter.as_deref(),
+            self.only_failed,
+            self.since.clone(),
+            self.result_path(),
+        )?;
+        // 获取当前工作目录，评测日志和编译结果都放在其中的 target 目录下
+        let current_dir = std::env::current_dir().context(t!("无法获取当前工作目录"))?;
+        let (mut grader, warnings) = self.options.grader(&self.config, &current_dir);
+        sandbox::report(&grader.sandbox, &warnings);
+        let reporter = Terminal::new(self.verbose);
+        let run_log = RunLog::start(&current_dir);
+        let log_dir = run_log.dir();
+        grader.log = Some(run_log);
+
         // 确定exercises目录
-        let absolute_path = current_dir.join(&self.path);
-        let exercises_dir = if absolute_path.ends_with("exercises") {
-            absolute_path.clone()
-        } else {
-            absolute_path.join("exercises")
-        };
+        let exercises_dir =
+            exercises_dir(&current_dir.join(self.path.as_ref().unwrap_or(&self.config.eval.path)));
 
         if !exercises_dir.exists() {
-            println!("{} {}", "警告:".yellow().bold(), "找不到exercises目录");
+            println!(
+                "{}",
+                t!("{} 找不到exercises目录", t!("警告:").yellow().bold())
+            );
             return Ok(());
         }
 
         let mut exercise_results = Vec::new();
-        let mut total_succeeds = 0;
-        let mut total_failures = 0;
-        let mut total_exercations = 0;
-
-        // 如果指定了course参数，只评测指定的课程
-        if let Some(course) = &self.course {
-            let course_path = exercises_dir.join(course);
-            if !course_path.exists() {
-                println!("{} {}", "警告:".yellow().bold(), format!("找不到课程目录: {}", course_path.display()));
-                return Ok(());
+
+        // 如果指定了course参数或在配置中指定了课程，只评测这些课程
+        let courses = match &self.course {
+            Some(course) => vec![course.clone()],
+            None => self.config.eval.courses.clone(),
+        };
+        for course in &courses {
+            let course = course_dir(&exercises_dir, course)?;
+            // 跳过无法评测的课程，其余课程照常评测并写入评测结果
+            if let Some(submodule) = &course.uninitialized {
+                warn_uninitialized(submodule);
+                continue;
+            }
+            if !course.path.exists() {
+                println!(
+                    "{}",
+                    t!(
+                        "{} 找不到课程目录: {}",
+                        t!("警告:").yellow().bold(),
+                        course.path.display()
+                    )
+                );
+                continue;
             }
 
-            println!("{} {}", "评测指定课程:".blue().bold(), course);
-            let (results, succeeds, failures, exercations) = match course.as_str() {
-                "learning-lm-rs" => self.eval_learning_lm(&course_path)?,
-                "learning-cxx" => self.eval_learning_cxx(&course_path)?,
-                _ => self.eval_rustlings(&course_path)?
-            };
-
-            exercise_results.extend(results);
-            total_succeeds += succeeds;
-            total_failures += failures;
-            total_exercations += exercations;
-        } else {
+            progress!("{} {}", t!("评测指定课程:").blue().bold(), course.name);
+            exercise_results.extend(self.grade_course(&grader, &course, &selection, &reporter)?);
+        }
+        if courses.is_empty() {
             // 自动评测所有课程
-            println!("{}", "自动评测所有课程...".blue().bold());
-            
-            // 获取exercises目录下的所有子目录
-            let entries = fs::read_dir(&exercises_dir)
-                .context(format!("无法读取目录: {}", exercises_dir.display()))?;
-
-            for entry in entries {
-                let entry = entry.context("读取目录项失败")?;
-                let path = entry.path();
-                
-                if path.is_dir() {
-                    let course_name = path.file_name()
-                        .and_then(|name| name.to_str())
-                        .unwrap_or("未知课程");
-                    
-                    println!("{} {}", "\n评测课程:".blue().bold(), course_name);
-                    
-                    let (results, succeeds, failures, exercations) = match course_name {
-                        "learning-lm-rs" => self.eval_learning_lm(&path)?,
-                        "learning-cxx" => self.eval_learning_cxx(&path)?,
-                        _ => self.eval_rustlings(&path)?
-                    };
-                    
-                    exercise_results.extend(results);
-                    total_succeeds += succeeds;
-                    total_failures += failures;
-                    total_exercations += exercations;
+            progress!("{}", t!("自动评测所有课程...").blue().bold());
+
+            for course in course_dirs(&exercises_dir)? {
+                if let Some(submodule) = &course.uninitialized {
+                    warn_uninitialized(submodule);
+                    continue;
                 }
+
+                progress!("{} {}", t!("\n评测课程:").blue().bold(), course.name);
+                exercise_results
+                    .extend(self.grade_course(&grader, &course, &selection, &reporter)?);
             }
         }
+        let total_exercations = exercise_results.len();
+        let total_succeeds = exercise_results.iter().filter(|e| e.result).count();
+        let total_failures = total_exercations - total_succeeds;
 
         let total_time = start_time.elapsed().as_secs();
 
         // 打印统计信息
-        println!("{}", "评测结果统计".green().bold());
-        println!("{}: {}", "总练习数".blue(), total_exercations);
-        println!("{}: {}", "通过数量".green(), total_succeeds);
-        println!("{}: {}", "失败数量".red(), total_failures);
-        println!("{}: {}秒", "总耗时".blue(), total_time);
+        println!("{}", t!("评测结果统计").green().bold());
+        println!("{}: {}", t!("总练习数").blue(), total_exercations);
+        println!("{}: {}", t!("通过数量").green(), total_succeeds);
+        println!("{}: {}", t!("失败数量").red(), total_failures);
+        println!("{}", t!("{}: {}秒", t!("总耗时").blue(), total_time));
 
         let pass_rate = if total_exercations > 0 {
             (total_succeeds as f32 / total_exercations as f32) * 100.0
         } else {
             0.0
         };
-        println!("{}: {:.2}%", "通过率".green(), pass_rate);
+        println!("{}: {:.2}%", t!("通过率").green(), pass_rate);
+
+        // 按测试计分，练习部分通过时也能看到进度
+        let tests = exercise_results
+            .iter()
+            .flat_map(|e| &e.tests)
+            .filter(|t| t.status != TestStatus::Ignored);
+        let total_tests = tests.clone().count();
+        if total_tests > 0 {
+            let passed_tests = tests.filter(|t| t.status == TestStatus::Passed).count();
+            println!(
+                "{}: {}/{}",
+                t!("通过测试").green(),
+                passed_tests,
+                total_tests
+            );
+        }
 
-        if total_failures > 0 {
-            println!("");
-            println!("{}", "失败的练习:".red().bold());
+        if exercise_results
+            .iter()
+            .any(|e| !e.result && e.error.is_none() && e.unfinished.is_none())
+        {
+            println!();
+            println!("{}", t!("失败的练习:").red().bold());
             for exercise in exercise_results.iter() {
-                if !exercise.result {
+                if !exercise.result && exercise.error.is_none() && exercise.unfinished.is_none() {
                     println!("  {}", exercise.name.red());
                 }
             }
         }
 
-        let result = GradeResult {
-            exercises: exercise_results,
-            statistics: Statistics {
-                total_exercations,
-                total_succeeds,
-                total_failures,
-                total_time,
-            },
-        };
-
-        // 使用固定的结果文件名
-        let result_filename = "eval_result.json";
-        let json_result = serde_json::to_string_pretty(&result)?;
-        fs::write(&result_filename, json_result)?;
-        println!("");
-        println!("{} {}", "评测结果已保存到".blue(), result_filename.blue());
-
-        Ok(())
-    }
-}
-
-
-
-/// 查找指定目录下的所有练习文件
-fn find_exercise_files(course_path: &Path, _course: &Option<String>) -> Result<Vec<PathBuf>> {
-    let mut exercise_files = Vec::new();
-    
-    if !course_path.exists() {
-        println!("{} {}", "警告:".yellow().bold(), format!("找不到课程目录: {}", course_path.display()));
-        return Ok(Vec::new());
-    }
-
-    // 对于learning-lm-rs项目，只返回model.rs和operators.rs
-    if course_path.file_name().map_or(false, |name| name == "learning-lm-rs") {
-        let src_path = course_path.join("src");
-        if src_path.exists() {
-            println!("{} {}", "找到learning-lm-rs项目:".blue().bold(), src_path.display());
-            let model_path = src_path.join("model.rs");
-            let operators_path = src_path.join("operators.rs");
-            
-            if model_path.exists() {
-                println!("{} {}", "找到练习文件:".blue(), model_path.display());
-                exercise_files.push(model_path);
-            } else {
-                println!("{} {}", "警告:".yellow().bold(), "找不到model.rs文件");
-            }
-            
-            if operators_path.exists() {
-                println!("{} {}", "找到练习文件:".blue(), operators_path.display());
-                exercise_files.push(operators_path);
-            } else {
-                println!("{} {}", "警告:".yellow().bold(), "找不到operators.rs文件");
-            }
-            
-            return Ok(exercise_files);
-        }
-    }
-
-    // 对于rustlings项目，只查找exercises目录下的文件
-    if course_path.file_name().map_or(false, |name| name == "rustlings") {
-        let exercises_path = course_path.join("exercises");
-        if !exercises_path.exists() {
-            println!("{} {}", "警告:".yellow().bold(), "找不到rustlings的exercises目录");
-            return Ok(Vec::new());
-        }
-
-        for entry in walkdir::WalkDir::new(&exercises_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
-                let file_name = path.file_name().unwrap().to_string_lossy();
-                if !file_name.starts_with("test_") && !file_name.starts_with("helper_") {
-                    exercise_files.push(path.to_path_buf());
+        // 评测出错的练习结果未知，与未通过的练习分开列出
+        if exercise_results.iter().any(|e| e.error.is_some()) {
+            println!();
+            println!("{}", t!("评测出错的练习:").yellow().bold());
+            for exercise in exercise_results.iter() {
+                if let Some(error) = &exercise.error {
+                    println!(
+                        "  {}: {}",
+                        exercise.name.yellow(),
+                        localize_text(error).lines().next().unwrap_or_default()
+                    );
                 }
             }
         }
-    } else {
-        // 对于其他项目，遍历目录查找练习文件
-        for entry in walkdir::WalkDir::new(course_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.components().any(|c| c.as_os_str() == "target") {
-                continue;
-            }
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
-                let file_name = path.file_name().unwrap().to_string_lossy();
-                if !file_name.starts_with("test_") && !file_name.starts_with("helper_") {
-                    exercise_files.push(path.to_path_buf());
+
+        // 尚未完成的练习没有评测，与做错的练习分开列出
+        if exercise_results.iter().any(|e| e.unfinished.is_some()) {
+            println!();
+            println!("{}", t!("尚未完成的练习:").yellow().bold());
+            for exercise in exercise_results.iter() {
+                if let Some(marker) = &exercise.unfinished {
+                    println!("  {}: {}", exercise.name.yellow(), localize_text(marker));
                 }
             }
         }
-    }
-
-    Ok(exercise_files)
-}
 
-/// 评测单个 Rustlings 练习文件 (不再处理 learning-lm-rs)
-fn grade_exercise(exercise_path: &Path, verbose: bool) -> Result<(String, bool, u64)> {
-    let start = Instant::now();
-    let exercise_name = exercise_path
-        .file_name()
-        .context("无法获取文件名")?
-        .to_string_lossy()
-        .to_string();
-
-    println!("{} {}", "评测练习:".blue().bold(), exercise_name);
-
-    // 检查是否是 clippy 练习
-    let is_clippy_exercise = exercise_path.to_string_lossy().contains("clippy");
-
-    // 如果是 clippy 练习，使用 cargo clippy 命令检查
-    if is_clippy_exercise {
-        // 创建一个临时目录来存放 Cargo.toml 和源文件
-        let temp_dir = tempfile::tempdir().context("创建临时目录失败")?;
-        let temp_dir_path = temp_dir.path();
-        
-        // 创建 Cargo.toml 文件
-        let cargo_toml_content = r#"[package]
-name = "clippy_check"
-version = "0.1.0"
-edition = "2021"
-
-[[bin]]
-name = "clippy_check"
-path = "src/main.rs"
-"#;
-        let cargo_toml_path = temp_dir_path.join("Cargo.toml");
-        fs::write(&cargo_toml_path, cargo_toml_content).context("写入 Cargo.toml 失败")?;
-        
-        // 创建 src 目录
-        let src_dir = temp_dir_path.join("src");
-        fs::create_dir(&src_dir).context("创建 src 目录失败")?;
-        
-        // 复制练习文件到 src/main.rs
-        let exercise_content = fs::read_to_string(exercise_path).context("读取练习文件失败")?;
-        let main_rs_path = src_dir.join("main.rs");
-        fs::write(&main_rs_path, exercise_content).context("写入 main.rs 失败")?;
-        
-        // 运行 cargo clippy
-        let clippy_output = Command::new("cargo")
-            .arg("clippy")
-            .arg("--manifest-path")
-            .arg(&cargo_toml_path)
-            .arg("--")
-            .arg("-D")
-            .arg("warnings")
-            .current_dir(temp_dir_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .context(format!("运行 cargo clippy 检查 {} 失败", exercise_name))?;
-        
-        let clippy_success = clippy_output.status.success();
-        
-        if !clippy_success {
-            if verbose {
-                println!("{}", String::from_utf8_lossy(&clippy_output.stdout));
-                println!("{}", String::from_utf8_lossy(&clippy_output.stderr));
+        // 测试代码被修改的练习，按 --tamper fail 判为未通过时也已列在上面
+        if exercise_results.iter().any(|e| !e.tampered.is_empty()) {
+            println!();
+            println!("{}", t!("测试代码被修改的练习:").yellow().bold());
+            for exercise in exercise_results.iter().filter(|e| !e.tampered.is_empty()) {
+                println!(
+                    "  {}: {}",
+                    exercise.name.yellow(),
+                    exercise.tampered.join(", ")
+                );
             }
-            println!("{} {}", "✗".red().bold(), exercise_name);
-            return Ok((exercise_name, false, start.elapsed().as_secs()));
         }
-    }
-
-    // 对于rustlings练习，直接使用rustc编译和运行测试
-    let test_output = Command::new("rustc")
-        .arg(exercise_path)
-        .arg("--test")
-        .arg("-o")
-        .arg(format!("target/debug/{}", exercise_name))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .context(format!("编译练习 {} 失败", exercise_name))?;
-
-    let success = test_output.status.success();
-
-    if !success {
-        if verbose {
-            println!("{}", String::from_utf8_lossy(&test_output.stdout));
-            println!("{}", String::from_utf8_lossy(&test_output.stderr));
-        }
-        println!("{} {}", "✗".red().bold(), exercise_name);
-        return Ok((exercise_name, false, start.elapsed().as_secs()));
-    }
 
-    // 编译成功，运行测试
-    let test_output = Command::new(format!("target/debug/{}", exercise_name))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .context(format!("运行练习 {} 失败", exercise_name))?;
-
-    let success = test_output.status.success();
+        let write = debug_span!("write").entered();
+        // 只评测了部分练习时，把本次结果合并进上一次的结果，保留未评测练习的记录
+        let exercise_results = if selection.is_active() {
+            merge_results(self.result_path(), exercise_results)?
+        } else {
+            exercise_results
+        };
+        let result = GradeResult::new(exercise_results, total_time);
 
-    if verbose || !success {
-        println!("{}", String::from_utf8_lossy(&test_output.stdout));
-        println!("{}", String::from_utf8_lossy(&test_output.stderr));
-    }
+        let result_path = self.result_path();
+        let json_result = serde_json::to_string_pretty(&result)?;
+        fs::write(result_path, json_result).context(t!("写入 {} 失败", result_path.display()))?;
+        drop(write);
+        println!();
+        println!(
+            "{} {}",
+            t!("评测结果已保存到").blue(),
+            result_path.display().to_string().blue()
+        );
+        // 没有评测任何练习时不会创建日志目录
+        if log_dir.is_dir() {
+            println!(
+                "{} {}",
+                t!("评测日志已保存到").blue(),
+                log_dir.display().to_string().blue()
+            );
+        }
 
-    if success {
-        println!("{} {}", "✓".green().bold(), exercise_name);
-    } else {
-        println!("{} {}", "✗".red().bold(), exercise_name);
+        Ok(())
     }
+}
 
-    Ok((exercise_name, success, start.elapsed().as_secs()))
+fn warn_uninitialized(submodule: &Submodule) {
+    println!(
+        "{}",
+        t!(
+            "{} 课程子模块 {} 尚未初始化 ({})，请先运行: git submodule update --init {}",
+            t!("警告:").yellow().bold(),
+            submodule.path,
+            submodule.url,
+            submodule.path
+        )
+    );
 }
@@ -0,0 +1,157 @@
+use clap::builder::{EnumValueParser, PossibleValue, TypedValueParser};
+use clap::{Command, ValueEnum};
+use grader::i18n::Lang;
+use std::sync::OnceLock;
+
+/// 命令行输出的语言，在解析命令行之前确定
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// 按输出语言翻译消息，参数按顺序替换 `{}`；不带参数时返回 `&'static str`，带参数时返回 `String`
+#[macro_export]
+macro_rules! t {
+    ($text:literal) => {
+        $crate::i18n::lang().tr($text)
+    };
+    ($text:literal, $($arg:expr),+ $(,)?) => {
+        grader::i18n::fill(
+            $crate::i18n::lang().tr($text),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+/// 确定输出语言：依次取 --lang、配置中的 lang 和环境变量 LC_ALL、LC_MESSAGES、LANG，都没有时使用中文
+///
+/// 显式的选择优先于系统区域设置。要在解析命令行之前调用，这样帮助信息也能使用所选的语言。
+pub fn init(config: Option<Lang>) -> Lang {
+    let lang = lang_arg()
+        .or(config)
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.is_empty())
+                .and_then(|value| from_locale(&value))
+        })
+        .unwrap_or_default();
+    *LANG.get_or_init(|| lang)
+}
+
+/// 命令行输出的语言，没有调用 [`init`] 时为中文
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// 按输出语言翻译库返回的文字，如评测结果中的原因和错误信息
+pub fn localize_text(text: &str) -> std::borrow::Cow<'_, str> {
+    lang().localize_text(text)
+}
+
+/// 命令行中的 --lang 参数，解析命令行之前就需要知道
+fn lang_arg() -> Option<Lang> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('=').map(str::to_string),
+            None => continue,
+        };
+        return value.and_then(|value| Lang::from_str(&value, true).ok());
+    }
+    None
+}
+
+/// 按 POSIX 区域设置选择语言；C 和 POSIX 不表示偏好，使用默认的中文
+fn from_locale(locale: &str) -> Option<Lang> {
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default();
+    match language {
+        "C" | "POSIX" => None,
+        "zh" => Some(Lang::ZhCn),
+        _ => Some(Lang::En),
+    }
+}
+
+/// 翻译命令、参数和子命令的帮助信息
+pub fn localize(mut command: Command) -> Command {
+    if lang() == Lang::ZhCn {
+        return command;
+    }
+    if let Some(about) = command
+        .get_about()
+        .and_then(|s| lang().translate(&s.to_string()))
+    {
+        command = command.about(about);
+    }
+    if let Some(about) = command
+        .get_long_about()
+        .and_then(|s| lang().translate(&s.to_string()))
+    {
+        command = command.long_about(about);
+    }
+    let args: Vec<_> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().clone())
+        .collect();
+    for id in args {
+        command = command.mut_arg(id, |mut arg| {
+            if let Some(help) = arg
+                .get_help()
+                .and_then(|s| lang().translate(&s.to_string()))
+            {
+                arg = arg.help(help);
+            }
+            if let Some(help) = arg
+                .get_long_help()
+                .and_then(|s| lang().translate(&s.to_string()))
+            {
+                arg = arg.long_help(help);
+            }
+            arg
+        });
+    }
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, localize);
+    }
+    command
+}
+
+/// 可选值的说明也经过翻译的枚举解析器，用法为 `#[clap(value_parser = localized::<E>())]`
+pub fn localized<E: ValueEnum + Clone + Send + Sync + 'static>() -> LocalizedEnum<E> {
+    LocalizedEnum(EnumValueParser::new())
+}
+
+#[derive(Clone)]
+pub struct LocalizedEnum<E: ValueEnum + Clone + Send + Sync + 'static>(EnumValueParser<E>);
+
+impl<E: ValueEnum + Clone + Send + Sync + 'static> TypedValueParser for LocalizedEnum<E> {
+    type Value = E;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<E, clap::Error> {
+        self.0.parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let values = self.0.possible_values()?.map(|value| {
+            match value
+                .get_help()
+                .and_then(|s| lang().translate(&s.to_string()))
+            {
+                Some(help) => value.help(help),
+                None => value,
+            }
+        });
+        Some(Box::new(values))
+    }
+}
@@ -1,170 +1,591 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use grader::files::copy_dir;
+use grader::gitmodules::GitModules;
 use std::fs;
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::i18n::localize_text;
+use crate::t;
+
+/// 支持的课程包格式，由系统的 tar 或 unzip 解压
+const ARCHIVE_EXTENSIONS: [&str; 6] = [".tar", ".tar.gz", ".tgz", ".tar.xz", ".tar.bz2", ".zip"];
+
 #[derive(Args)]
 pub struct LearnArgs {
     /// 课程名称
     course: String,
-    /// 传入 fork 仓库地址，以 git submodule 方式配置
+    /// 传入 fork 仓库地址或本地仓库路径，以 git submodule 方式配置
     #[clap(long)]
     submodule: Option<String>,
+    /// 从本地目录、裸仓库或 tar/zip 课程包配置课程，适用于离线环境
+    #[clap(long, conflicts_with = "submodule")]
+    local: Option<PathBuf>,
+    /// 以普通目录方式复制课程，而不是配置为 git submodule
+    #[clap(long)]
+    vendor: bool,
+}
+
+/// 课程来源
+enum CourseSource {
+    /// 远程仓库地址
+    Remote(String),
+    /// 本地 git 仓库（含裸仓库）
+    GitRepo(PathBuf),
+    /// 不受 git 管理的本地目录
+    Directory(PathBuf),
+    /// tar/zip 课程包
+    Archive(PathBuf),
 }
 
 impl LearnArgs {
     pub fn learn(self) {
         if let Err(e) = self.run_learn() {
-            eprintln!("{} {}", "配置课程失败:".red().bold(), e);
+            eprintln!(
+                "{} {}",
+                t!("配置课程失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
         }
     }
 
     fn run_learn(&self) -> Result<()> {
-        println!("{} {}", "开始配置课程:".blue().bold(), self.course);
+        println!("{} {}", t!("开始配置课程:").blue().bold(), self.course);
 
         // 确保exercises目录存在
         let exercises_dir = Path::new("exercises");
         if !exercises_dir.exists() {
-            fs::create_dir_all(exercises_dir).context("创建exercises目录失败")?;
+            fs::create_dir_all(exercises_dir).context(t!("创建exercises目录失败"))?;
         }
 
-        // 如果提供了子模块地址，则克隆仓库
-        if let Some(repo_url) = &self.submodule {
-            println!("{} {}", "克隆仓库:".blue().bold(), repo_url);
+        let source = match (&self.submodule, &self.local) {
+            (Some(repo_url), _) if !Path::new(repo_url).exists() => {
+                CourseSource::Remote(repo_url.clone())
+            }
+            (Some(path), _) => CourseSource::detect(Path::new(path))?,
+            (None, Some(path)) => CourseSource::detect(path)?,
+            (None, None) => {
+                println!(
+                    "{}",
+                    t!("未提供仓库地址，请使用 --submodule 参数指定仓库地址，或使用 --local 指定本地课程").yellow()
+                );
+                return Ok(());
+            }
+        };
 
-            let course_dir = exercises_dir.join(&self.course);
-            let submodule_path = format!("exercises/{}", self.course);
+        match source {
+            CourseSource::Remote(url) if !self.vendor => self.learn_submodule(&url, false)?,
+            CourseSource::GitRepo(path) if !self.vendor => {
+                // 子模块地址使用绝对路径，避免相对路径随工作目录变化
+                let path =
+                    fs::canonicalize(&path).context(t!("无法解析路径 {}", path.display()))?;
+                self.learn_submodule(&path.to_string_lossy(), true)?;
+            }
+            source => {
+                if !self.vendor {
+                    println!(
+                        "{}",
+                        t!("课程来源不是 git 仓库，将以普通目录方式配置").yellow()
+                    );
+                }
+                self.learn_vendored(&source, Path::new("."))?;
+            }
+        }
 
-            // 检查子模块是否已在 .gitmodules 中配置
-            let gitmodules_path = Path::new(".gitmodules");
-            let submodule_exists_in_config = if gitmodules_path.exists() {
-                let content =
-                    fs::read_to_string(gitmodules_path).context("读取 .gitmodules 文件失败")?;
-                content.contains(&format!("path = {}", submodule_path))
-            } else {
-                false
-            };
+        println!("{} {}", t!("成功配置课程:").green().bold(), self.course);
+        println!("{} exercises/{}", t!("练习已克隆到:").green(), self.course);
+        println!(
+            "{}",
+            t!("你现在可以使用 'cargo xtask eval' 命令来评测练习").blue()
+        );
+
+        Ok(())
+    }
+
+    /// 以 git submodule 方式配置课程，`local` 表示地址是本机路径
+    fn learn_submodule(&self, repo_url: &str, local: bool) -> Result<()> {
+        println!("{} {}", t!("克隆仓库:").blue().bold(), repo_url);
+
+        let course_dir = Path::new("exercises").join(&self.course);
+        let submodule_path = format!("exercises/{}", self.course);
+
+        // 检查子模块是否已在 .gitmodules 中配置
+        let gitmodules_path = Path::new(".gitmodules");
+        let gitmodules = GitModules::load(gitmodules_path)?;
 
-            if submodule_exists_in_config {
+        if let Some(submodule) = gitmodules.find_by_path(&submodule_path) {
+            println!(
+                "{} {}",
+                t!("子模块已配置，更新到最新版本").yellow().bold(),
+                self.course
+            );
+
+            // 同一路径配置了不同的仓库地址，通常是学员换了 fork
+            if !submodule.same_url(repo_url) {
                 println!(
-                    "{} {}",
-                    "子模块已配置，更新到最新版本".yellow().bold(),
-                    self.course
+                    "{}",
+                    t!(
+                        "{} 子模块 {} 当前地址为 {}，与传入的 {} 不一致",
+                        t!("警告:").yellow().bold(),
+                        submodule_path,
+                        submodule.url,
+                        repo_url
+                    )
                 );
-
-                // 如果目录存在，先删除以确保完全重新克隆
-                if course_dir.exists() {
-                    fs::remove_dir_all(&course_dir)
-                        .context(format!("删除已存在的目录 {} 失败", course_dir.display()))?;
+                if confirm(t!("是否将子模块地址更新为传入的仓库地址?"))? {
+                    GitModules::set_url(gitmodules_path, &submodule.name, repo_url)?;
+                    sync_submodule_url(&submodule.name, &submodule_path, repo_url)?;
+                    println!("{} {}", t!("子模块地址已更新为:").green(), repo_url);
+                } else {
+                    println!("{}", t!("保留原有子模块地址").yellow());
                 }
+            }
 
-                // 初始化并更新子模块到最新版本，并设置为跟踪远程分支
-                let status = Command::new("git")
-                    .args(["submodule", "update", "--init", "--remote", &submodule_path])
-                    .status()
-                    .context("执行git submodule update命令失败")?;
+            // 如果目录存在，先删除以确保完全重新克隆
+            if course_dir.exists() {
+                fs::remove_dir_all(&course_dir)
+                    .context(t!("删除已存在的目录 {} 失败", course_dir.display()))?;
+            }
 
-                if !status.success() {
-                    return Err(anyhow::anyhow!("git submodule update命令执行失败"));
-                }
+            // 初始化并更新子模块到最新版本，并设置为跟踪远程分支
+            let status = git_command(local)
+                .args(["submodule", "update", "--init", "--remote", &submodule_path])
+                .status()
+                .context(t!("执行git submodule update命令失败"))?;
 
-                // 切换到主分支并设置跟踪远程分支
-                let status = Command::new("git")
-                    .current_dir(&course_dir)
-                    .args(["checkout", "-B", "master", "origin/master"])
-                    .status()
-                    .context("切换到master分支失败")?;
-
-                if !status.success() {
-                    // 如果master不存在，尝试main分支
-                    let status = Command::new("git")
-                        .current_dir(&course_dir)
-                        .args(["checkout", "-B", "main", "origin/main"])
-                        .status()
-                        .context("切换到main分支失败")?;
-
-                    if !status.success() {
-                        println!(
-                            "{}",
-                            "警告: 无法切换到主分支，子模块将保持在detached HEAD状态".yellow()
-                        );
-                    }
-                }
-            } else {
-                // 如果目录存在但子模块未配置，先删除目录
-                if course_dir.exists() {
-                    println!(
-                        "{} {}",
-                        "警告:".yellow().bold(),
-                        format!("目录 {} 已存在，将被覆盖", course_dir.display())
-                    );
-                    fs::remove_dir_all(&course_dir)
-                        .context(format!("删除已存在的目录 {} 失败", course_dir.display()))?;
-                }
+            if !status.success() {
+                return Err(anyhow::anyhow!(t!("git submodule update命令执行失败")));
+            }
 
-                // 添加新的子模块
-                let status = Command::new("git")
-                    .args(["submodule", "add", repo_url, &submodule_path])
-                    .status()
-                    .context("执行git submodule add命令失败")?;
+            checkout_main_branch(&course_dir)?;
+        } else {
+            // 如果目录存在但子模块未配置，先删除目录
+            if course_dir.exists() {
+                println!(
+                    "{}",
+                    t!(
+                        "{} 目录 {} 已存在，将被覆盖",
+                        t!("警告:").yellow().bold(),
+                        course_dir.display()
+                    )
+                );
+                fs::remove_dir_all(&course_dir)
+                    .context(t!("删除已存在的目录 {} 失败", course_dir.display()))?;
+            }
 
-                if !status.success() {
-                    return Err(anyhow::anyhow!("git submodule add命令执行失败"));
-                }
+            // 添加新的子模块
+            let status = git_command(local)
+                .args(["submodule", "add", repo_url, &submodule_path])
+                .status()
+                .context(t!("执行git submodule add命令失败"))?;
 
-                // 确保子模块是最新的并切换到主分支
-                let status = Command::new("git")
-                    .args(["submodule", "update", "--init", "--remote", &submodule_path])
-                    .status()
-                    .context("执行git submodule update命令失败")?;
+            if !status.success() {
+                return Err(anyhow::anyhow!(t!("git submodule add命令执行失败")));
+            }
 
-                if !status.success() {
-                    return Err(anyhow::anyhow!("初始化子模块到最新版本失败"));
-                }
+            // 确保子模块是最新的并切换到主分支
+            let status = git_command(local)
+                .args(["submodule", "update", "--init", "--remote", &submodule_path])
+                .status()
+                .context(t!("执行git submodule update命令失败"))?;
 
-                // 切换到主分支并设置跟踪远程分支
-                let status = Command::new("git")
-                    .current_dir(&course_dir)
-                    .args(["checkout", "-B", "master", "origin/master"])
-                    .status()
-                    .context("切换到master分支失败")?;
-
-                if !status.success() {
-                    // 如果master不存在，尝试main分支
-                    let status = Command::new("git")
-                        .current_dir(&course_dir)
-                        .args(["checkout", "-B", "main", "origin/main"])
-                        .status()
-                        .context("切换到main分支失败")?;
-
-                    if !status.success() {
-                        println!(
-                            "{}",
-                            "警告: 无法切换到主分支，子模块将保持在detached HEAD状态".yellow()
-                        );
-                    }
-                }
+            if !status.success() {
+                return Err(anyhow::anyhow!(t!("初始化子模块到最新版本失败")));
             }
 
-            println!("{} {}", "成功配置课程:".green().bold(), self.course);
+            checkout_main_branch(&course_dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// 把课程复制为工作区 exercises 下的普通目录
+    fn learn_vendored(&self, source: &CourseSource, workspace_root: &Path) -> Result<()> {
+        let course_dir = workspace_root.join("exercises").join(&self.course);
+        let submodule_path = format!("exercises/{}", self.course);
+
+        // 原来以子模块方式配置过的课程，先移除子模块登记
+        let gitmodules = GitModules::load(&workspace_root.join(".gitmodules"))?;
+        if let Some(submodule) = gitmodules.find_by_path(&submodule_path) {
             println!(
                 "{} {}",
-                "练习已克隆到:".green(),
-                format!("exercises/{}", self.course)
+                t!("移除已配置的子模块:").yellow().bold(),
+                submodule_path
             );
+            let status = Command::new("git")
+                .current_dir(workspace_root)
+                .args(["rm", "-q", "-f", "--", &submodule_path])
+                .status()
+                .context(t!("执行git rm命令失败"))?;
+            if !status.success() {
+                return Err(anyhow::anyhow!(t!("移除子模块 {} 失败", submodule_path)));
+            }
+            // 保留的子模块仓库会导致以后无法以同名重新添加子模块
+            let module_git_dir = workspace_root
+                .join(".git")
+                .join("modules")
+                .join(&submodule.name);
+            if module_git_dir.exists() {
+                fs::remove_dir_all(&module_git_dir)
+                    .context(t!("删除 {} 失败", module_git_dir.display()))?;
+            }
+        }
+
+        if course_dir.exists() {
             println!(
                 "{}",
-                "你现在可以使用 'cargo xtask eval' 命令来评测练习".blue()
+                t!(
+                    "{} 目录 {} 已存在，将被覆盖",
+                    t!("警告:").yellow().bold(),
+                    course_dir.display()
+                )
             );
+            fs::remove_dir_all(&course_dir)
+                .context(t!("删除已存在的目录 {} 失败", course_dir.display()))?;
+        }
+
+        match source {
+            CourseSource::Remote(url) => clone_without_history(url, &course_dir, false)?,
+            CourseSource::GitRepo(path) => {
+                clone_without_history(&path.to_string_lossy(), &course_dir, true)?
+            }
+            CourseSource::Directory(path) => {
+                println!("{} {}", t!("复制目录:").blue().bold(), path.display());
+                copy_dir(path, &course_dir)?;
+            }
+            CourseSource::Archive(path) => {
+                println!("{} {}", t!("解压课程包:").blue().bold(), path.display());
+                let temp_dir = tempfile::tempdir().context(t!("创建临时目录失败"))?;
+                extract_archive(path, temp_dir.path())?;
+                copy_dir(&archive_root(temp_dir.path())?, &course_dir)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CourseSource {
+    /// 根据本地路径判断课程来源的类型
+    fn detect(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(anyhow::anyhow!(t!("找不到本地课程: {}", path.display())));
+        }
+        if path.is_file() {
+            return if is_archive(path) {
+                Ok(Self::Archive(path.to_path_buf()))
+            } else {
+                Err(anyhow::anyhow!(t!(
+                    "不支持的课程包格式: {}，请使用 {}",
+                    path.display(),
+                    ARCHIVE_EXTENSIONS.join(", ")
+                )))
+            };
+        }
+        let is_bare = path.join("HEAD").is_file()
+            && path.join("objects").is_dir()
+            && path.join("refs").is_dir();
+        if path.join(".git").exists() || is_bare {
+            Ok(Self::GitRepo(path.to_path_buf()))
         } else {
+            Ok(Self::Directory(path.to_path_buf()))
+        }
+    }
+}
+
+/// 本地仓库需要允许 file 协议，新版 git 默认禁止子模块使用本地路径
+fn git_command(local: bool) -> Command {
+    let mut command = Command::new("git");
+    if local {
+        command.args(["-c", "protocol.file.allow=always"]);
+    }
+    command
+}
+
+/// 克隆仓库后删除 .git，只保留工作区文件
+fn clone_without_history(url: &str, course_dir: &Path, local: bool) -> Result<()> {
+    println!("{} {}", t!("克隆仓库:").blue().bold(), url);
+    let status = git_command(local)
+        .args(["clone", "--depth", "1", url])
+        .arg(course_dir)
+        .status()
+        .context(t!("执行git clone命令失败"))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(t!("git clone命令执行失败")));
+    }
+    fs::remove_dir_all(course_dir.join(".git")).context(t!("删除课程目录中的 .git 失败"))?;
+    Ok(())
+}
+
+fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// 使用系统的 tar / unzip 解压课程包
+fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let is_zip = archive
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let status = if is_zip {
+        Command::new("unzip")
+            .arg("-q")
+            .arg(archive)
+            .arg("-d")
+            .arg(dest)
+            .status()
+            .context(t!("执行 unzip 失败，请确认已安装 unzip"))?
+    } else {
+        Command::new("tar")
+            .arg("-xf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dest)
+            .status()
+            .context(t!("执行 tar 失败，请确认已安装 tar"))?
+    };
+    if !status.success() {
+        return Err(anyhow::anyhow!(t!("解压课程包 {} 失败", archive.display())));
+    }
+    Ok(())
+}
+
+/// 课程包通常只包含一个顶层目录，此时以该目录作为课程根目录
+fn archive_root(extracted: &Path) -> Result<PathBuf> {
+    let entries: Vec<_> = fs::read_dir(extracted)
+        .context(t!("读取解压目录失败"))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok(entry.path()),
+        _ => Ok(extracted.to_path_buf()),
+    }
+}
+
+/// 切换到主分支并设置跟踪远程分支，master 不存在时尝试 main
+fn checkout_main_branch(course_dir: &Path) -> Result<()> {
+    let status = Command::new("git")
+        .current_dir(course_dir)
+        .args(["checkout", "-B", "master", "origin/master"])
+        .status()
+        .context(t!("切换到master分支失败"))?;
+
+    if !status.success() {
+        let status = Command::new("git")
+            .current_dir(course_dir)
+            .args(["checkout", "-B", "main", "origin/main"])
+            .status()
+            .context(t!("切换到main分支失败"))?;
+
+        if !status.success() {
             println!(
                 "{}",
-                "未提供仓库地址，请使用 --submodule 参数指定仓库地址".yellow()
+                t!("警告: 无法切换到主分支，子模块将保持在detached HEAD状态").yellow()
             );
         }
+    }
+    Ok(())
+}
 
-        Ok(())
+/// 将 .gitmodules 中的新地址同步到 .git/config 和子模块自身的 origin
+fn sync_submodule_url(name: &str, submodule_path: &str, url: &str) -> Result<()> {
+    let status = Command::new("git")
+        .args(["submodule", "sync", "--", submodule_path])
+        .status()
+        .context(t!("执行git submodule sync命令失败"))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(t!("git submodule sync命令执行失败")));
+    }
+
+    // 工作目录未检出时 sync 不会修改子模块仓库的 origin，这里直接改
+    let module_git_dir = Path::new(".git").join("modules").join(name);
+    if module_git_dir.exists() {
+        let status = Command::new("git")
+            .arg("--git-dir")
+            .arg(&module_git_dir)
+            .args(["remote", "set-url", "origin", url])
+            .status()
+            .context(t!("更新子模块 origin 地址失败"))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(t!("更新子模块 origin 地址失败")));
+        }
+    }
+    Ok(())
+}
+
+/// 在终端询问是/否，非交互环境下默认为否
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush().context(t!("刷新标准输出失败"))?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context(t!("读取输入失败"))?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "YES"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn run(command: &mut Command) {
+        let output = command.output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn git(dir: &Path) -> Command {
+        let mut command = Command::new("git");
+        command.current_dir(dir).args([
+            "-c",
+            "user.name=xtask",
+            "-c",
+            "user.email=xtask@example.com",
+        ]);
+        command
+    }
+
+    /// 只有一个练习的课程
+    fn course(dir: &Path) {
+        write(&dir.join("info.toml"), "[[exercises]]\nname = \"intro1\"\n");
+        write(&dir.join("exercises/intro1.rs"), "fn main() {}\n");
+    }
+
+    /// 带一次提交的课程仓库的裸仓库
+    fn bare_repo(dir: &Path) -> PathBuf {
+        let repo = dir.join("repo");
+        course(&repo);
+        run(git(dir).args(["init", "-q", "repo"]));
+        run(git(&repo).args(["add", "-A"]));
+        run(git(&repo).args(["commit", "-q", "-m", "init"]));
+        let bare = dir.join("course.git");
+        run(git(dir).args(["clone", "-q", "--bare", "repo", "course.git"]));
+        bare
+    }
+
+    /// 把 `dir` 下的 `entries` 打包为 tar.gz
+    fn tarball(dir: &Path, entries: &[&str], archive: &Path) {
+        run(Command::new("tar")
+            .arg("-czf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dir)
+            .args(entries));
+    }
+
+    fn learn(course: &str) -> LearnArgs {
+        LearnArgs {
+            course: course.to_string(),
+            submodule: None,
+            local: None,
+            vendor: true,
+        }
+    }
+
+    #[test]
+    fn detect_source_kinds() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let plain = dir.path().join("plain");
+        course(&plain);
+        assert!(matches!(
+            CourseSource::detect(&plain).unwrap(),
+            CourseSource::Directory(_)
+        ));
+
+        let bare = bare_repo(dir.path());
+        assert!(matches!(
+            CourseSource::detect(&bare).unwrap(),
+            CourseSource::GitRepo(_)
+        ));
+        assert!(matches!(
+            CourseSource::detect(&dir.path().join("repo")).unwrap(),
+            CourseSource::GitRepo(_)
+        ));
+
+        let archive = dir.path().join("course.tar.gz");
+        tarball(dir.path(), &["plain"], &archive);
+        assert!(matches!(
+            CourseSource::detect(&archive).unwrap(),
+            CourseSource::Archive(_)
+        ));
+
+        let unsupported = dir.path().join("course.rar");
+        fs::write(&unsupported, "").unwrap();
+        assert!(CourseSource::detect(&unsupported).is_err());
+        assert!(CourseSource::detect(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn archive_root_unwraps_a_single_top_level_directory() {
+        let nested = tempfile::tempdir().unwrap();
+        course(&nested.path().join("rustlings-main"));
+        assert_eq!(
+            archive_root(nested.path()).unwrap(),
+            nested.path().join("rustlings-main")
+        );
+
+        let flat = tempfile::tempdir().unwrap();
+        course(flat.path());
+        assert_eq!(archive_root(flat.path()).unwrap(), flat.path());
+
+        // 只有一个文件时不是顶层目录
+        let single_file = tempfile::tempdir().unwrap();
+        write(&single_file.path().join("info.toml"), "");
+        assert_eq!(
+            archive_root(single_file.path()).unwrap(),
+            single_file.path()
+        );
+    }
+
+    #[test]
+    fn vendored_courses_are_copied_from_every_source_kind() {
+        let sources = tempfile::tempdir().unwrap();
+        let plain = sources.path().join("plain");
+        course(&plain);
+        let bare = bare_repo(sources.path());
+        let nested = sources.path().join("nested.tar.gz");
+        tarball(sources.path(), &["plain"], &nested);
+        let flat = sources.path().join("flat.tar.gz");
+        tarball(&plain, &["info.toml", "exercises"], &flat);
+
+        let cases = [
+            ("directory", CourseSource::detect(&plain).unwrap()),
+            ("bare", CourseSource::detect(&bare).unwrap()),
+            ("nested", CourseSource::detect(&nested).unwrap()),
+            ("flat", CourseSource::detect(&flat).unwrap()),
+        ];
+        let workspace = tempfile::tempdir().unwrap();
+        for (name, source) in cases {
+            learn(name)
+                .learn_vendored(&source, workspace.path())
+                .unwrap();
+            let course_dir = workspace.path().join("exercises").join(name);
+            assert!(course_dir.join("exercises/intro1.rs").is_file(), "{}", name);
+            assert!(!course_dir.join(".git").exists(), "{}", name);
+        }
+
+        // 再次配置时覆盖已有的目录
+        fs::write(plain.join("exercises/intro1.rs"), "// updated\n").unwrap();
+        learn("directory")
+            .learn_vendored(&CourseSource::Directory(plain), workspace.path())
+            .unwrap();
+        let updated = workspace
+            .path()
+            .join("exercises/directory/exercises/intro1.rs");
+        assert_eq!(fs::read_to_string(updated).unwrap(), "// updated\n");
     }
 }
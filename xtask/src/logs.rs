@@ -0,0 +1,218 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::eval::GradeResult;
+use grader::logs::LOG_DIR;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::i18n::localize_text;
+use crate::t;
+
+#[derive(Args)]
+pub struct LogArgs {
+    /// 练习名称，例如 variables1、variables1.rs 或 exercise05
+    exercise: String,
+
+    /// 练习所在的课程，多个课程中有同名练习时需要指定
+    #[clap(long)]
+    course: Option<String>,
+}
+
+impl LogArgs {
+    pub fn log(self) {
+        if let Err(e) = self.run_log() {
+            eprintln!(
+                "{} {}",
+                t!("查看日志失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_log(&self) -> Result<()> {
+        let result_path = Config::load()?.eval.result;
+        let result = fs::read_to_string(&result_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<GradeResult>(&content).ok());
+        let path = self.find_log(Path::new("."), result.as_ref())?;
+        println!("{} {}", t!("评测日志:").blue().bold(), path.display());
+        let content = fs::read(&path).context(t!("读取 {} 失败", path.display()))?;
+        io::stdout()
+            .write_all(&content)
+            .context(t!("输出日志失败"))?;
+        Ok(())
+    }
+
+    /// 优先使用评测结果中记录的日志，评测结果中没有时在最近的日志目录中查找
+    ///
+    /// 日志路径相对 `workspace_root`。没有指定课程而练习出现在多个课程中时报错。
+    fn find_log(&self, workspace_root: &Path, result: Option<&GradeResult>) -> Result<PathBuf> {
+        if let Some(result) = result {
+            let logged: Vec<(String, PathBuf)> = result
+                .exercises
+                .iter()
+                .filter(|exercise| self.matches(&exercise.course, &exercise.name))
+                .filter_map(|exercise| {
+                    let log = workspace_root.join(exercise.log.as_ref()?);
+                    log.is_file().then(|| (exercise.course.clone(), log))
+                })
+                .collect();
+            if let Some(log) = self.unique(logged)? {
+                return Ok(log);
+            }
+        }
+
+        let mut runs: Vec<PathBuf> = fs::read_dir(workspace_root.join(LOG_DIR))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        runs.sort();
+        for run in runs.iter().rev() {
+            let mut logged = Vec::new();
+            for course in fs::read_dir(run).into_iter().flatten().flatten() {
+                let course_name = course.file_name().to_string_lossy().to_string();
+                for log in fs::read_dir(course.path()).into_iter().flatten().flatten() {
+                    let log = log.path();
+                    let name = log.file_stem().unwrap_or_default().to_string_lossy();
+                    if self.matches(&course_name, &name) {
+                        logged.push((course_name.clone(), log));
+                    }
+                }
+            }
+            if let Some(log) = self.unique(logged)? {
+                return Ok(log);
+            }
+        }
+        match &self.course {
+            Some(course) => bail!(t!(
+                "找不到课程 {} 中练习 {} 的评测日志",
+                course,
+                self.exercise
+            )),
+            None => bail!(t!("找不到练习 {} 的评测日志", self.exercise)),
+        }
+    }
+
+    /// 找到的日志只能来自一个课程，否则要求用 --course 指定
+    fn unique(&self, mut logged: Vec<(String, PathBuf)>) -> Result<Option<PathBuf>> {
+        let mut courses: Vec<&str> = logged.iter().map(|(course, _)| course.as_str()).collect();
+        courses.sort();
+        courses.dedup();
+        if courses.len() > 1 {
+            bail!(t!(
+                "多个课程中都有练习 {}: {}，请用 --course 指定课程",
+                self.exercise,
+                courses.join(", ")
+            ));
+        }
+        Ok((!logged.is_empty()).then(|| logged.swap_remove(0).1))
+    }
+
+    /// 练习名称可以带或不带扩展名；指定了课程时只看这个课程，旧版评测结果没有课程，不区分
+    fn matches(&self, course: &str, name: &str) -> bool {
+        let course_matches = match &self.course {
+            Some(expected) => course.is_empty() || course == expected,
+            None => true,
+        };
+        course_matches
+            && (name == self.exercise
+                || Path::new(name)
+                    .file_stem()
+                    .is_some_and(|stem| stem == self.exercise.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grader::eval::ExerciseResult;
+
+    fn args(exercise: &str, course: Option<&str>) -> LogArgs {
+        LogArgs {
+            exercise: exercise.to_string(),
+            course: course.map(str::to_string),
+        }
+    }
+
+    /// 在工作区中写入一份日志，返回相对工作区的路径
+    fn write_log(workspace: &Path, run: &str, course: &str, exercise: &str) -> String {
+        let relative = format!("{}/{}/{}/{}.log", LOG_DIR, run, course, exercise);
+        let path = workspace.join(&relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "==> run\n").unwrap();
+        relative
+    }
+
+    fn logged(course: &str, name: &str, log: &str) -> ExerciseResult {
+        ExerciseResult {
+            name: name.to_string(),
+            course: course.to_string(),
+            log: Some(log.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn result_logs_are_looked_up_by_name_and_course() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        let rustlings = write_log(root, "20240101-000000", "rustlings", "intro1");
+        let other = write_log(root, "20240101-000000", "rust-exercises", "intro1");
+        let result = GradeResult::new(
+            vec![
+                logged("rustlings", "intro1.rs", &rustlings),
+                logged("rust-exercises", "intro1.rs", &other),
+                logged("rustlings", "intro2.rs", "target/xtask-logs/missing.log"),
+            ],
+            0,
+        );
+
+        let find = |exercise, course| args(exercise, course).find_log(root, Some(&result));
+        assert_eq!(
+            find("intro1", Some("rustlings")).unwrap(),
+            root.join(&rustlings)
+        );
+        assert_eq!(
+            find("intro1.rs", Some("rust-exercises")).unwrap(),
+            root.join(&other)
+        );
+        let error = find("intro1", None).unwrap_err().to_string();
+        assert!(error.contains("rust-exercises, rustlings"), "{}", error);
+        // 评测结果中记录的日志已被清理
+        assert!(find("intro2", None).is_err());
+    }
+
+    #[test]
+    fn latest_run_is_searched_without_a_result() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        write_log(root, "20240101-000000", "learning-cxx", "exercise05");
+        let latest = write_log(root, "20240102-000000", "learning-cxx", "exercise05");
+        write_log(root, "20240102-000000", "learning-cxx", "exercise06");
+        write_log(root, "20240103-000000", "rustlings", "intro1");
+
+        let find = |exercise, course| args(exercise, course).find_log(root, None);
+        assert_eq!(find("exercise05", None).unwrap(), root.join(latest));
+        assert_eq!(
+            find("exercise05", Some("rustlings"))
+                .unwrap_err()
+                .to_string(),
+            "找不到课程 rustlings 中练习 exercise05 的评测日志"
+        );
+
+        write_log(root, "20240104-000000", "rustlings", "exercise05");
+        write_log(root, "20240104-000000", "learning-cxx", "exercise05");
+        assert!(find("exercise05", None).is_err());
+        assert_eq!(
+            find("exercise05", Some("learning-cxx")).unwrap(),
+            root.join(format!(
+                "{}/20240104-000000/learning-cxx/exercise05.log",
+                LOG_DIR
+            ))
+        );
+    }
+}
@@ -1,21 +1,68 @@
+mod config;
 mod eval;
+mod i18n;
 mod learn;
+mod logs;
+mod next;
+mod output;
+mod report;
+mod reset;
+mod sandbox;
 mod setup;
+mod snapshot;
+mod submit;
+mod sync;
+mod tamper;
+mod trace;
+mod watch;
 
-use clap::Parser;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser};
+use colored::*;
+use grader::i18n::Lang;
+use std::path::PathBuf;
+use config::ConfigArgs;
 use eval::EvalArgs;
 use learn::LearnArgs;
+use logs::LogArgs;
+use output::ColorChoice;
+use next::{HintArgs, NextArgs};
+use reset::ResetArgs;
 use setup::SetupArgs;
+use snapshot::{RestoreArgs, SnapshotArgs, SnapshotsArgs};
+use submit::SubmitArgs;
+use sync::SyncArgs;
+use tamper::ChecksumsArgs;
+use watch::WatchArgs;
 
 #[macro_use]
 extern crate clap;
 
 fn main() {
     use Commands::*;
-    match Cli::parse().command {
+    // 帮助信息在解析命令行时输出，需要先确定语言
+    i18n::init(config::Config::load().ok().and_then(|config| config.lang));
+    let matches = i18n::localize(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    output::init(cli.color);
+    if let Err(e) = trace::init(cli.verbose, cli.quiet, cli.log_file.as_deref()) {
+        eprintln!("{} {:#}", t!("警告:").yellow().bold(), e);
+    }
+    match cli.command {
         Setup(args) => args.setup(),
         Learn(args) => args.learn(),
         Eval(args) => args.eval(),
+        Submit(args) => args.submit(),
+        Watch(args) => args.watch(),
+        Next(args) => args.next(),
+        Hint(args) => args.hint(),
+        Sync(args) => args.sync(),
+        Snapshot(args) => args.snapshot(),
+        Restore(args) => args.restore(),
+        Snapshots(args) => args.snapshots(),
+        Reset(args) => args.reset(),
+        Checksums(args) => args.checksums(),
+        Config(args) => args.config(),
+        Log(args) => args.log(),
     }
 }
 
@@ -25,6 +72,27 @@ fn main() {
 struct Cli {
     #[clap(subcommand)]
     command: Commands,
+
+    /// 输出语言，默认取 LANG 等环境变量或 xtask.toml 中的设置
+    #[clap(long, global = true, value_parser = i18n::localized::<Lang>())]
+    #[allow(dead_code)] // 已在解析命令行之前读取
+    lang: Option<Lang>,
+
+    /// 输出更多信息：-v 显示各阶段的耗时和学员程序的完整输出，-vv 显示调试信息
+    #[clap(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// 只输出错误，评测时只输出评测结果统计
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// 何时输出颜色
+    #[clap(long, global = true, value_parser = i18n::localized::<ColorChoice>(), default_value = "auto")]
+    color: ColorChoice,
+
+    /// 把运行日志以 JSON Lines 格式追加到文件，记录所有级别
+    #[clap(long, global = true)]
+    log_file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -35,4 +103,28 @@ enum Commands {
     Learn(LearnArgs),
     /// 评分
     Eval(EvalArgs),
+    /// 把评测结果提交到 xtask.toml 中配置的地址
+    Submit(SubmitArgs),
+    /// 监视课程目录，保存文件后自动评测
+    Watch(WatchArgs),
+    /// 显示第一个未通过的练习及其提示
+    Next(NextArgs),
+    /// 显示练习的逐级提示
+    Hint(HintArgs),
+    /// 从课程模板仓库同步新练习
+    Sync(SyncArgs),
+    /// 保存课程练习的快照
+    Snapshot(SnapshotArgs),
+    /// 从快照恢复课程练习
+    Restore(RestoreArgs),
+    /// 列出已保存的快照
+    Snapshots(SnapshotsArgs),
+    /// 将单个练习恢复为模板版本
+    Reset(ResetArgs),
+    /// 为课程模板生成测试代码校验和清单，提交到模板仓库后评测时用于发现被修改的测试
+    Checksums(ChecksumsArgs),
+    /// 查看 xtask.toml 配置
+    Config(ConfigArgs),
+    /// 显示练习最近一次评测的完整日志
+    Log(LogArgs),
 }
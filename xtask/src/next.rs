@@ -0,0 +1,452 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::course::{InfoFile, Unit, course_units};
+use grader::discover::exercises_dir;
+use grader::eval::{ExerciseResult, GradeResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::i18n::localize_text;
+use crate::t;
+
+/// learning-cxx、learning-lm-rs 等课程的提示文件目录，位于课程根目录下
+const HINTS_DIR: &str = "hints";
+
+#[derive(Args)]
+pub struct NextArgs {
+    /// 课程名称，不传则按课程目录顺序查找
+    #[clap(long)]
+    course: Option<String>,
+}
+
+#[derive(Args)]
+pub struct HintArgs {
+    /// 练习名称，例如 variables1、exercise05，不传则为当前练习
+    exercise: Option<String>,
+    /// 课程名称，不传则在所有课程中查找
+    #[clap(long)]
+    course: Option<String>,
+    /// 显示前几级提示
+    #[clap(long, default_value_t = 1)]
+    level: usize,
+}
+
+/// 某个课程中的一个练习
+struct Current {
+    course: String,
+    course_path: PathBuf,
+    unit: Unit,
+}
+
+impl NextArgs {
+    pub fn next(self) {
+        if let Err(e) = self.run_next() {
+            eprintln!(
+                "{} {}",
+                t!("查找下一个练习失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_next(&self) -> Result<()> {
+        let results = previous_results()?;
+        let exercises_dir = configured_exercises_dir()?;
+        let Some(current) = first_unsolved(&exercises_dir, self.course.as_deref(), &results)?
+        else {
+            println!("{}", t!("恭喜，所有练习都已通过!").green().bold());
+            return Ok(());
+        };
+
+        println!(
+            "{} {} ({})",
+            t!("当前练习:").yellow().bold(),
+            current.unit.name(),
+            current.unit.location().display()
+        );
+        match previous(&results, &current.course, &current.unit.name()) {
+            Some(ExerciseResult {
+                error: Some(error), ..
+            }) => {
+                println!("{}", t!("上次评测出错，原因:").yellow().bold());
+                print_indented(&localize_text(error));
+            }
+            Some(ExerciseResult {
+                unfinished: Some(marker),
+                ..
+            }) => {
+                println!("{}", t!("练习尚未完成:").yellow().bold());
+                print_indented(&t!(
+                    "{}，完成后删除标记或替换占位代码",
+                    localize_text(marker)
+                ));
+            }
+            Some(result) => {
+                println!("{}", t!("上次评测未通过，原因:").red().bold());
+                print_indented(&localize_text(
+                    result.reason.as_deref().unwrap_or("未知原因"),
+                ));
+            }
+            None => println!("{}", t!("尚未评测，请运行 cargo xtask eval").blue()),
+        }
+        print_hint(&current, 1)
+    }
+}
+
+impl HintArgs {
+    pub fn hint(self) {
+        if let Err(e) = self.run_hint() {
+            eprintln!(
+                "{} {}",
+                t!("获取提示失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_hint(&self) -> Result<()> {
+        if self.level == 0 {
+            bail!(t!("提示级别从 1 开始"));
+        }
+        let exercises_dir = configured_exercises_dir()?;
+        let course = self.course.as_deref();
+        let current = match &self.exercise {
+            Some(exercise) => find_unit(&exercises_dir, course, exercise)?,
+            None => match first_unsolved(&exercises_dir, course, &previous_results()?)? {
+                Some(current) => current,
+                None => {
+                    println!("{}", t!("恭喜，所有练习都已通过!").green().bold());
+                    return Ok(());
+                }
+            },
+        };
+
+        println!(
+            "{} {} ({})",
+            t!("练习:").blue().bold(),
+            current.unit.name(),
+            current.unit.location().display()
+        );
+        print_hint(&current, self.level)
+    }
+}
+
+/// 读取上一次的评测结果；没有评测过时为空
+fn previous_results() -> Result<Vec<ExerciseResult>> {
+    let config = Config::load()?;
+    let path = config.eval.result.as_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).context(t!("读取 {} 失败", path.display()))?;
+    let result: GradeResult =
+        serde_json::from_str(&content).context(t!("解析 {} 失败", path.display()))?;
+    Ok(result.exercises)
+}
+
+/// 上一次评测结果中 `course` 课程的 `name` 练习
+fn previous<'a>(
+    results: &'a [ExerciseResult],
+    course: &str,
+    name: &str,
+) -> Option<&'a ExerciseResult> {
+    results
+        .iter()
+        .find(|result| result.same_exercise(course, name))
+}
+
+/// exercises 目录，与 eval 一样取自配置中的 eval.path
+fn configured_exercises_dir() -> Result<PathBuf> {
+    Ok(exercises_dir(&Config::load()?.eval.path))
+}
+
+/// 要查找的课程：指定了就只看这一个，否则按目录名顺序列出 exercises 下已配置的课程
+fn courses(exercises_dir: &Path, course: Option<&str>) -> Result<Vec<(String, PathBuf)>> {
+    if let Some(course) = course {
+        let course_path = exercises_dir.join(course);
+        if !course_path.is_dir() {
+            bail!(t!("找不到课程目录: {}", course_path.display()));
+        }
+        return Ok(vec![(course.to_string(), course_path)]);
+    }
+
+    let mut courses = Vec::new();
+    if exercises_dir.is_dir() {
+        for entry in fs::read_dir(exercises_dir).context(t!("无法读取 exercises 目录"))? {
+            let path = entry.context(t!("读取目录项失败"))?.path();
+            // 未初始化的子模块是空目录
+            let is_empty = fs::read_dir(&path).map_or(true, |mut entries| entries.next().is_none());
+            if path.is_dir() && !is_empty {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                courses.push((name, path));
+            }
+        }
+    }
+    if courses.is_empty() {
+        bail!(t!(
+            "exercises 目录下没有已配置的课程，请先运行 cargo xtask learn"
+        ));
+    }
+    courses.sort();
+    Ok(courses)
+}
+
+/// 按课程顺序找到第一个上次未通过或尚未评测的练习
+fn first_unsolved(
+    exercises_dir: &Path,
+    course: Option<&str>,
+    results: &[ExerciseResult],
+) -> Result<Option<Current>> {
+    for (course, course_path) in courses(exercises_dir, course)? {
+        let unit = course_units(&course, &course_path)?
+            .into_iter()
+            .find(|unit| !previous(results, &course, &unit.name()).is_some_and(|r| r.result));
+        if let Some(unit) = unit {
+            return Ok(Some(Current {
+                course,
+                course_path,
+                unit,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// 按练习名称查找，接受 variables1、variables1.rs、exercise05 或 05 等写法
+fn find_unit(exercises_dir: &Path, course: Option<&str>, exercise: &str) -> Result<Current> {
+    for (course, course_path) in courses(exercises_dir, course)? {
+        let unit = course_units(&course, &course_path)?
+            .into_iter()
+            .find(|unit| {
+                let name = unit.name();
+                name == exercise
+                    || unit.hint_key() == exercise
+                    || name.strip_prefix("exercise") == Some(exercise)
+            });
+        if let Some(unit) = unit {
+            return Ok(Current {
+                course,
+                course_path,
+                unit,
+            });
+        }
+    }
+    bail!(t!("找不到练习: {}", exercise))
+}
+
+/// 练习的逐级提示
+///
+/// rustlings 使用 info.toml 中的 hint 字段，按空行分段；
+/// 其他课程使用 `hints/<练习>.md`，以单独一行的 `---` 分隔各级提示。
+fn hint_levels(current: &Current) -> Result<Vec<String>> {
+    let key = current.unit.hint_key();
+
+    if let Some(info) = InfoFile::load(&current.course_path)?
+        && let Some(exercise) = info.exercises.into_iter().find(|e| e.name == key)
+    {
+        return Ok(split_levels(&exercise.hint, |line| line.trim().is_empty()));
+    }
+
+    let hints_dir = current.course_path.join(HINTS_DIR);
+    for file_name in [format!("{}.md", key), format!("{}.md", current.unit.name())] {
+        let path = hints_dir.join(file_name);
+        if path.is_file() {
+            let content = fs::read_to_string(&path).context(t!("读取 {} 失败", path.display()))?;
+            return Ok(split_levels(&content, |line| line.trim() == "---"));
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// 按分隔行切分提示，丢弃空的段落
+fn split_levels(text: &str, is_separator: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut levels = vec![String::new()];
+    for line in text.lines() {
+        if is_separator(line) {
+            levels.push(String::new());
+        } else if let Some(level) = levels.last_mut() {
+            level.push_str(line);
+            level.push('\n');
+        }
+    }
+    levels
+        .into_iter()
+        .map(|level| level.trim().to_string())
+        .filter(|level| !level.is_empty())
+        .collect()
+}
+
+fn print_hint(current: &Current, level: usize) -> Result<()> {
+    let levels = hint_levels(current)?;
+    if levels.is_empty() {
+        if current.course_path.join("info.toml").exists() {
+            println!(
+                "{}",
+                t!(
+                    "{} {} 没有提示",
+                    t!("提示:").yellow().bold(),
+                    current.unit.name()
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                t!(
+                    "{} {} 没有提示，可以在 {} 中添加",
+                    t!("提示:").yellow().bold(),
+                    current.unit.name(),
+                    current
+                        .course_path
+                        .join(HINTS_DIR)
+                        .join(format!("{}.md", current.unit.hint_key()))
+                        .display()
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    let shown = level.min(levels.len());
+    for (index, hint) in levels.iter().take(shown).enumerate() {
+        println!(
+            "{}",
+            t!("提示 ({}/{}):", index + 1, levels.len()).yellow().bold()
+        );
+        print_indented(hint);
+    }
+    if shown < levels.len() {
+        println!(
+            "{} cargo xtask hint {} --course {} --level {}",
+            t!("查看下一级提示:").blue(),
+            current.unit.hint_key(),
+            current.course,
+            shown + 1
+        );
+    }
+    Ok(())
+}
+
+fn print_indented(text: &str) {
+    for line in text.lines() {
+        println!("  {}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_levels_drops_empty_sections() {
+        let blank = |line: &str| line.trim().is_empty();
+        assert_eq!(
+            split_levels("first\nstill first\n\n  \nsecond\n", blank),
+            ["first\nstill first", "second"]
+        );
+        assert!(split_levels("", blank).is_empty());
+
+        let rule = |line: &str| line.trim() == "---";
+        assert_eq!(
+            split_levels("---\n看看 main.cpp\n\n注意类型\n---\n---\n答案 ---\n", rule),
+            ["看看 main.cpp\n\n注意类型", "答案 ---"]
+        );
+    }
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// 一个 rustlings 课程和一个 learning-cxx 课程
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let rustlings = dir.path().join("rustlings");
+        write(
+            &rustlings.join("info.toml"),
+            r#"
+            [[exercises]]
+            name = "variables1"
+            path = "exercises/variables1.rs"
+            mode = "compile"
+            hint = """
+            第一级
+
+            第二级"""
+            "#,
+        );
+        write(&rustlings.join("exercises/variables1.rs"), "fn main() {}\n");
+
+        let cxx = dir.path().join("learning-cxx");
+        write(
+            &cxx.join("exercises/05_pointer/main.cpp"),
+            "int main() {}\n",
+        );
+        write(&cxx.join("exercises/06_array/main.cpp"), "int main() {}\n");
+        write(&cxx.join("hints/05_pointer.md"), "按目录名\n---\n第二级\n");
+        write(&cxx.join("hints/exercise06.md"), "按练习名\n");
+        dir
+    }
+
+    #[test]
+    fn exercises_are_found_by_any_alias() {
+        let dir = workspace();
+        let found = |course, exercise| {
+            let current = find_unit(dir.path(), course, exercise).unwrap();
+            (current.course, current.unit.name())
+        };
+        let pointer = ("learning-cxx".to_string(), "exercise05".to_string());
+        assert_eq!(found(None, "05"), pointer);
+        assert_eq!(found(None, "exercise05"), pointer);
+        assert_eq!(found(None, "05_pointer"), pointer);
+        assert_eq!(found(Some("learning-cxx"), "05"), pointer);
+
+        let variables = ("rustlings".to_string(), "variables1.rs".to_string());
+        assert_eq!(found(None, "variables1"), variables);
+        assert_eq!(found(None, "variables1.rs"), variables);
+
+        assert!(find_unit(dir.path(), Some("rustlings"), "05").is_err());
+        assert!(find_unit(dir.path(), Some("missing"), "05").is_err());
+    }
+
+    #[test]
+    fn hints_come_from_info_toml_or_hint_files() {
+        let dir = workspace();
+        let hints =
+            |exercise| hint_levels(&find_unit(dir.path(), None, exercise).unwrap()).unwrap();
+        assert_eq!(hints("variables1"), ["第一级", "第二级"]);
+        // hints/<目录名>.md 优先，没有时用 hints/<练习名>.md
+        assert_eq!(hints("05"), ["按目录名", "第二级"]);
+        assert_eq!(hints("06"), ["按练习名"]);
+
+        fs::remove_file(dir.path().join("learning-cxx/hints/exercise06.md")).unwrap();
+        assert!(hints("06").is_empty());
+    }
+
+    #[test]
+    fn first_unsolved_skips_passed_exercises() {
+        let dir = workspace();
+        let passed = |course: &str, name: &str| ExerciseResult {
+            name: name.to_string(),
+            course: course.to_string(),
+            result: true,
+            ..Default::default()
+        };
+        let results = [passed("learning-cxx", "exercise05")];
+        let current = first_unsolved(dir.path(), None, &results).unwrap().unwrap();
+        assert_eq!(current.unit.name(), "exercise06");
+
+        let results = [
+            passed("learning-cxx", "exercise05"),
+            passed("learning-cxx", "exercise06"),
+        ];
+        let current = first_unsolved(dir.path(), None, &results).unwrap().unwrap();
+        assert_eq!(current.course, "rustlings");
+        assert!(
+            first_unsolved(dir.path(), Some("learning-cxx"), &results)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
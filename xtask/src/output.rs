@@ -0,0 +1,179 @@
+use clap::ValueEnum;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+
+use crate::trace;
+
+/// 何时输出颜色
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// 输出到终端且没有设置 NO_COLOR 时使用颜色
+    #[default]
+    Auto,
+    /// 总是使用颜色，例如 CI 的日志页面能显示颜色时
+    Always,
+    /// 不使用颜色
+    Never,
+}
+
+/// 按 --color 设置颜色
+pub fn init(color: ColorChoice) {
+    colored::control::set_override(Terminal::current().color(color));
+}
+
+/// 是否只输出评测结果统计，由全局的 -q 决定
+pub fn quiet() -> bool {
+    trace::quiet()
+}
+
+/// 是否显示进度条，见 [`Terminal::progress`]
+pub fn interactive() -> bool {
+    Terminal::current().progress(quiet())
+}
+
+/// 决定颜色和进度条的环境变量，以及标准输出、标准错误是否为终端
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Terminal {
+    /// 设置了 NO_COLOR
+    no_color: bool,
+    /// CLICOLOR 为 0
+    clicolor_off: bool,
+    /// CLICOLOR_FORCE 为 0 以外的值
+    clicolor_force: bool,
+    stdout_tty: bool,
+    stderr_tty: bool,
+}
+
+impl Terminal {
+    fn current() -> Self {
+        Self::new(
+            |name| std::env::var_os(name),
+            std::io::stdout().is_terminal(),
+            std::io::stderr().is_terminal(),
+        )
+    }
+
+    fn new(var: impl Fn(&str) -> Option<OsString>, stdout_tty: bool, stderr_tty: bool) -> Self {
+        Terminal {
+            no_color: var("NO_COLOR").is_some(),
+            clicolor_off: var("CLICOLOR").is_some_and(|value| value == "0"),
+            clicolor_force: var("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+            stdout_tty,
+            stderr_tty,
+        }
+    }
+
+    /// 是否使用颜色；auto 时与 colored 的规则相同：CLICOLOR_FORCE 优先，其次是 NO_COLOR 和
+    /// CLICOLOR=0，否则看标准输出是否为终端
+    fn color(&self, choice: ColorChoice) -> bool {
+        match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if self.clicolor_force => true,
+            ColorChoice::Auto if self.no_color || self.clicolor_off => false,
+            ColorChoice::Auto => self.stdout_tty,
+        }
+    }
+
+    /// 标准输出和标准错误都是终端时才显示进度条，输出被重定向（如 `| tee`）时进度条会和其他输出混在一起
+    fn progress(&self, quiet: bool) -> bool {
+        !quiet && self.stdout_tty && self.stderr_tty
+    }
+}
+
+/// 评测过程中的进度信息，与 `println!` 相同，-q 时不输出
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::output::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminal(vars: &[(&str, &str)], stdout_tty: bool, stderr_tty: bool) -> Terminal {
+        Terminal::new(
+            |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            },
+            stdout_tty,
+            stderr_tty,
+        )
+    }
+
+    #[test]
+    fn color_follows_the_flag_then_the_environment() {
+        let cases = [
+            (ColorChoice::Auto, vec![], true, true),
+            (ColorChoice::Auto, vec![], false, false),
+            (ColorChoice::Auto, vec![("NO_COLOR", "1")], true, false),
+            // NO_COLOR 只要设置了就生效
+            (ColorChoice::Auto, vec![("NO_COLOR", "")], true, false),
+            (ColorChoice::Auto, vec![("CLICOLOR", "0")], true, false),
+            (ColorChoice::Auto, vec![("CLICOLOR", "1")], true, true),
+            (
+                ColorChoice::Auto,
+                vec![("CLICOLOR_FORCE", "1")],
+                false,
+                true,
+            ),
+            (
+                ColorChoice::Auto,
+                vec![("CLICOLOR_FORCE", "0")],
+                false,
+                false,
+            ),
+            (
+                ColorChoice::Auto,
+                vec![("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")],
+                false,
+                true,
+            ),
+            (ColorChoice::Always, vec![("NO_COLOR", "1")], false, true),
+            (
+                ColorChoice::Never,
+                vec![("CLICOLOR_FORCE", "1")],
+                true,
+                false,
+            ),
+        ];
+        for (choice, vars, stdout_tty, expected) in cases {
+            assert_eq!(
+                terminal(&vars, stdout_tty, true).color(choice),
+                expected,
+                "{:?} {:?} stdout_tty = {}",
+                choice,
+                vars,
+                stdout_tty
+            );
+        }
+    }
+
+    #[test]
+    fn progress_needs_both_streams_on_a_terminal_and_no_quiet() {
+        let cases = [
+            (false, true, true, true),
+            (true, true, true, false),
+            (false, false, true, false),
+            (false, true, false, false),
+        ];
+        for (quiet, stdout_tty, stderr_tty, expected) in cases {
+            assert_eq!(
+                terminal(&[], stdout_tty, stderr_tty).progress(quiet),
+                expected,
+                "quiet = {} stdout_tty = {} stderr_tty = {}",
+                quiet,
+                stdout_tty,
+                stderr_tty
+            );
+        }
+        // 进度条不受颜色设置影响
+        assert!(terminal(&[("NO_COLOR", "1")], true, true).progress(false));
+    }
+}
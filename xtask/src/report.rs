@@ -0,0 +1,193 @@
+use colored::*;
+use grader::diagnostics::Diagnostic;
+use grader::eval::ExerciseResult;
+use grader::libtest::{TestResult, TestStatus};
+use grader::report::{Evaluator, Event, Reporter};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::cell::RefCell;
+
+use crate::i18n::localize_text;
+use crate::output;
+use crate::progress;
+use crate::t;
+
+/// 在终端中显示评测进度，-q 时不输出
+pub struct Terminal {
+    /// 显示构建和测试的完整输出，以及通过的测试
+    verbose: bool,
+    /// 当前课程的进度条，输出被重定向或 -q 时隐藏
+    bar: RefCell<Option<ProgressBar>>,
+}
+
+impl Terminal {
+    pub fn new(verbose: bool) -> Self {
+        Terminal {
+            verbose,
+            bar: RefCell::new(None),
+        }
+    }
+
+    /// 结束当前课程的进度条
+    pub fn finish(&self) {
+        if let Some(bar) = self.bar.borrow_mut().take() {
+            bar.finish();
+        }
+    }
+
+    /// 输出与进度条交替显示，不混在同一行
+    fn print(&self, f: impl FnOnce()) {
+        match self.bar.borrow().as_ref() {
+            Some(bar) => bar.suspend(f),
+            None => f(),
+        }
+    }
+
+    fn course(&self, evaluator: Evaluator) {
+        match evaluator {
+            Evaluator::LearningLm => {
+                progress!("{}", t!("评测 learning-lm-rs 项目...").blue().bold())
+            }
+            Evaluator::LearningCxx(_) => {
+                progress!("{}", t!("评测 learning-cxx 项目...").blue().bold())
+            }
+            Evaluator::Rustlings { cargo } => {
+                progress!("{}", t!("评测 rustlings 项目...").blue().bold());
+                if cargo {
+                    progress!(
+                        "{}",
+                        t!("使用课程的 Cargo.toml 构建和评测练习...").blue().bold()
+                    );
+                } else {
+                    progress!("{}", t!("使用 rustc 编译和运行测试来评测...").blue().bold());
+                }
+            }
+            Evaluator::Generic(language) => progress!(
+                "{} {}",
+                t!("评测课程，识别为:").blue().bold(),
+                language.name()
+            ),
+        }
+    }
+
+    fn found(&self, count: usize) {
+        progress!(
+            "{} {} {}",
+            t!("找到").blue().bold(),
+            count,
+            t!("个练习文件").blue().bold()
+        );
+        let bar = if output::interactive() {
+            ProgressBar::new(count as u64)
+        } else {
+            ProgressBar::hidden()
+        };
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+            )
+            .unwrap()
+            .progress_chars("##-"),
+        );
+        self.finish();
+        *self.bar.borrow_mut() = Some(bar);
+    }
+
+    fn graded(&self, result: &ExerciseResult) {
+        if let Some(marker) = &result.unfinished {
+            progress!(
+                "{} {} {}",
+                "…".yellow().bold(),
+                result.name,
+                t!("尚未完成: {}", localize_text(marker)).yellow()
+            );
+            return;
+        }
+        if let Some(error) = &result.error {
+            progress!(
+                "{} {} {}",
+                "!".yellow().bold(),
+                result.name,
+                t!("评测出错").yellow()
+            );
+            for line in localize_text(error).lines() {
+                progress!("    {}", line.dimmed());
+            }
+            return;
+        }
+
+        if result.result {
+            progress!("{} {}", "✓".green().bold(), result.name);
+        } else {
+            progress!("{} {}", "✗".red().bold(), result.name);
+        }
+        if !result.tampered.is_empty() {
+            progress!(
+                "{} {} {}",
+                "!".yellow().bold(),
+                result.name,
+                t!("测试代码与模板不一致: {}", result.tampered.join(", ")).yellow()
+            );
+        }
+        if !result.diagnostics.is_empty() {
+            print_diagnostics(&result.diagnostics);
+        } else if !result.tests.is_empty() {
+            print_tests(&result.tests, self.verbose);
+        } else if !result.result {
+            for line in localize_text(result.reason.as_deref().unwrap_or_default()).lines() {
+                progress!("    {}", line.dimmed());
+            }
+        }
+    }
+}
+
+impl Reporter for Terminal {
+    fn report(&self, event: Event) {
+        match event {
+            Event::Course(evaluator) => self.course(evaluator),
+            Event::Found(count) => self.found(count),
+            Event::Exercise(name) => {
+                self.print(|| progress!("{} {}", t!("评测练习:").blue().bold(), name))
+            }
+            Event::Running(command) => {
+                self.print(|| progress!("{} {}", t!("运行测试:").blue().bold(), command))
+            }
+            Event::Output(text) if self.verbose => self.print(|| progress!("{}", text)),
+            Event::Output(_) => {}
+            Event::Graded(result) => {
+                self.print(|| self.graded(result));
+                if let Some(bar) = self.bar.borrow().as_ref() {
+                    bar.inc(1);
+                }
+            }
+        }
+    }
+}
+
+/// 在终端中紧凑地显示诊断
+pub fn print_diagnostics(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        progress!("  {}", diagnostic.headline().red());
+        for line in diagnostic.details() {
+            progress!("    {}", localize_text(&line).dimmed());
+        }
+    }
+}
+
+/// 在终端中显示各个测试的结果，`all` 为 false 时只显示失败的测试
+pub fn print_tests(tests: &[TestResult], all: bool) {
+    for test in tests {
+        match test.status {
+            TestStatus::Passed if all => progress!("  {} {}", "✓".green(), test.name),
+            TestStatus::Ignored if all => progress!("  {} {} (ignored)", "-".yellow(), test.name),
+            TestStatus::Failed => {
+                progress!("  {} {}", "✗".red(), test.name);
+                if let Some(message) = &test.message {
+                    for line in message.lines() {
+                        progress!("      {}", line.dimmed());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
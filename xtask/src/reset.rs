@@ -0,0 +1,146 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::course::course_units;
+use grader::files::copy_dir;
+use grader::git::{git, git_ok, template_rev};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::localize_text;
+use crate::t;
+
+/// 被重置练习的备份位置
+const BACKUPS_DIR: &str = ".xtask/backups";
+
+#[derive(Args)]
+pub struct ResetArgs {
+    /// 课程名称
+    course: String,
+    /// 练习名称，例如 variables1、variables1.rs、exercise05 或练习的相对路径
+    exercise: String,
+    /// 指定模板版本（提交、分支或标签），默认从课程仓库的历史中推断
+    #[clap(long)]
+    rev: Option<String>,
+}
+
+impl ResetArgs {
+    pub fn reset(self) {
+        if let Err(e) = self.run_reset() {
+            eprintln!(
+                "{} {}",
+                t!("重置练习失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_reset(&self) -> Result<()> {
+        let course_dir = Path::new("exercises").join(&self.course);
+        if !course_dir.is_dir() {
+            bail!(t!("找不到课程目录: {}", course_dir.display()));
+        }
+        if !git_ok(&course_dir, &["rev-parse", "--git-dir"])? {
+            bail!(t!(
+                "{} 不是 git 仓库，无法确定练习的模板版本",
+                course_dir.display()
+            ));
+        }
+
+        let exercise = find_exercise(&self.course, &course_dir, &self.exercise)?;
+        let rev = match &self.rev {
+            Some(rev) => rev.clone(),
+            None => template_rev(&course_dir)?,
+        };
+        let short_rev = git(&course_dir, &["rev-parse", "--short", &rev])?;
+        println!(
+            "{} {} ({})",
+            t!("重置练习:").blue().bold(),
+            exercise.display(),
+            short_rev
+        );
+
+        // 路径相对课程目录，`./` 让 git 按当前目录解析
+        let pathspec = format!("./{}", exercise.to_string_lossy().replace('\\', "/"));
+        let object = format!("{}:{}", rev, pathspec);
+        if !git_ok(&course_dir, &["cat-file", "-e", &object])? {
+            bail!(t!("模板版本 {} 中不存在 {}", short_rev, exercise.display()));
+        }
+
+        let backup = backup_exercise(&self.course, &course_dir, &exercise)?;
+        println!(
+            "{} {}",
+            t!("已备份当前版本到:").yellow().bold(),
+            backup.display()
+        );
+
+        git(
+            &course_dir,
+            &["restore", "--source", &rev, "--worktree", "--", &pathspec],
+        )?;
+        println!(
+            "{} {}",
+            t!("练习已恢复为模板版本:").green().bold(),
+            exercise.display()
+        );
+        Ok(())
+    }
+}
+
+/// 在课程的练习中查找，返回相对课程目录的路径（文件或目录）
+///
+/// 与 eval 使用同一份练习列表，`solutions/` 中同名的参考答案等其他文件不会被匹配到。
+fn find_exercise(course: &str, course_dir: &Path, exercise: &str) -> Result<PathBuf> {
+    // 直接给出了相对路径
+    if course_dir.join(exercise).exists() && exercise.contains(['/', '\\']) {
+        return Ok(PathBuf::from(exercise));
+    }
+
+    // learning-cxx 的练习在评测结果中记作 exerciseNN，也接受只写编号
+    let mut candidates: Vec<PathBuf> = course_units(course, course_dir)?
+        .iter()
+        .filter(|unit| {
+            let name = unit.name();
+            name == exercise
+                || unit.hint_key() == exercise
+                || name.strip_prefix("exercise") == Some(exercise)
+        })
+        .map(|unit| unit.relative(course_dir))
+        .collect();
+    candidates.dedup();
+
+    match candidates.len() {
+        0 => bail!(t!("找不到练习: {}", exercise)),
+        1 => Ok(candidates.remove(0)),
+        _ => {
+            let list = candidates
+                .iter()
+                .map(|c| format!("  {}", c.display()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!(t!(
+                "练习名称 {} 匹配到多个路径，请使用相对路径:\n{}",
+                exercise,
+                list
+            ))
+        }
+    }
+}
+
+/// 把练习当前的版本复制到备份目录
+fn backup_exercise(course: &str, course_dir: &Path, exercise: &Path) -> Result<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    let backup_root = Path::new(BACKUPS_DIR).join(course).join(timestamp);
+    let source = course_dir.join(exercise);
+    let target = backup_root.join(exercise);
+
+    if source.is_dir() {
+        copy_dir(&source, &target)?;
+    } else if source.is_file() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).context(t!("创建目录 {} 失败", parent.display()))?;
+        }
+        fs::copy(&source, &target).context(t!("备份 {} 失败", source.display()))?;
+    }
+    Ok(target)
+}
@@ -0,0 +1,167 @@
+use clap::Args;
+use colored::*;
+use grader::sandbox::{Limits, Sandbox, bwrap_usable, timeout_usable};
+
+use crate::config::Config;
+use crate::progress;
+use crate::t;
+
+/// 运行学员代码的选项：沙箱、超时和构建并行数，未指定的选项取 xtask.toml 中的设置
+#[derive(Args, Clone, Debug)]
+pub struct SandboxOptions {
+    /// 在沙箱中运行学员代码：课程源码只读、私有临时目录、无网络，并限制内存、CPU 时间和进程数
+    #[clap(long)]
+    pub sandbox: bool,
+
+    /// 不使用沙箱，即使配置中启用了沙箱
+    #[clap(long, conflicts_with = "sandbox")]
+    pub no_sandbox: bool,
+
+    /// 沙箱中每个进程的内存上限（MiB），默认为 4096
+    #[clap(long)]
+    pub sandbox_memory: Option<u64>,
+
+    /// 沙箱中每个进程的 CPU 时间上限（秒），默认为 120
+    #[clap(long)]
+    pub sandbox_cpu: Option<u64>,
+
+    /// 沙箱中的进程数上限，默认为 1024；只在 bubblewrap 沙箱中生效
+    #[clap(long)]
+    pub sandbox_processes: Option<u64>,
+
+    /// 每个构建、测试或运行命令的时间上限（秒），超时的命令会被终止；不使用沙箱时也有效
+    #[clap(long)]
+    pub timeout: Option<u64>,
+
+    /// cargo 构建的并行数
+    #[clap(long)]
+    pub jobs: Option<usize>,
+}
+
+impl SandboxOptions {
+    /// 合并命令行和配置中的选项，同时返回给用户的警告
+    ///
+    /// 检查 bubblewrap 和 timeout 命令能否使用，不能使用时退回只限制资源或不限制时间。
+    pub fn sandbox(&self, config: &Config) -> (Sandbox, Vec<String>) {
+        self.resolve(config, bwrap_usable, timeout_usable)
+    }
+
+    fn resolve(
+        &self,
+        config: &Config,
+        bwrap_usable: impl FnOnce() -> bool,
+        timeout_usable: impl FnOnce() -> bool,
+    ) -> (Sandbox, Vec<String>) {
+        let mut warnings = Vec::new();
+        let timeout = match self.timeout.or(config.eval.timeout) {
+            Some(_) if !timeout_usable() => {
+                warnings.push(t!("找不到 timeout 命令，不限制命令的运行时间").to_string());
+                None
+            }
+            timeout => timeout,
+        };
+        let jobs = self.jobs.or(config.eval.jobs);
+        if !(self.sandbox || config.sandbox.enabled && !self.no_sandbox) {
+            let sandbox = Sandbox {
+                limits: None,
+                bwrap: false,
+                timeout,
+                jobs,
+            };
+            return (sandbox, warnings);
+        }
+        let bwrap = bwrap_usable();
+        if !bwrap {
+            let warning = t!(
+                "找不到可用的 bwrap（或系统禁用了非特权用户命名空间），沙箱只限制内存和 CPU 时间，不隔离文件系统和网络"
+            );
+            warnings.push(warning.to_string());
+        }
+        let sandbox = Sandbox {
+            limits: Some(Limits {
+                memory_mb: self.sandbox_memory.unwrap_or(config.sandbox.memory),
+                cpu_seconds: self.sandbox_cpu.unwrap_or(config.sandbox.cpu),
+                processes: self.sandbox_processes.unwrap_or(config.sandbox.processes),
+            }),
+            bwrap,
+            timeout,
+            jobs,
+        };
+        (sandbox, warnings)
+    }
+}
+
+/// 显示评测使用的沙箱和 [`SandboxOptions::sandbox`] 返回的警告
+pub fn report(sandbox: &Sandbox, warnings: &[String]) {
+    for warning in warnings {
+        println!("{} {}", t!("警告:").yellow().bold(), warning);
+    }
+    if sandbox.bwrap {
+        progress!("{}", t!("在 bubblewrap 沙箱中运行练习").blue().bold());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[clap(flatten)]
+        options: SandboxOptions,
+    }
+
+    fn resolve(
+        args: &[&str],
+        config: &Config,
+        bwrap: bool,
+        timeout: bool,
+    ) -> (Sandbox, Vec<String>) {
+        let cli = Cli::parse_from(std::iter::once("xtask").chain(args.iter().copied()));
+        cli.options.resolve(config, || bwrap, || timeout)
+    }
+
+    #[test]
+    fn sandbox_is_enabled_by_flag_or_config() {
+        let mut config = Config::default();
+        let (sandbox, warnings) = resolve(&[], &config, true, true);
+        assert!(sandbox.limits.is_none() && !sandbox.bwrap);
+        assert!(warnings.is_empty());
+
+        let (sandbox, _) = resolve(
+            &["--sandbox", "--sandbox-memory", "256"],
+            &config,
+            true,
+            true,
+        );
+        assert!(sandbox.bwrap);
+        let limits = sandbox.limits.unwrap();
+        assert_eq!(limits.memory_mb, 256);
+        assert_eq!(limits.cpu_seconds, config.sandbox.cpu);
+
+        config.sandbox.enabled = true;
+        assert!(resolve(&[], &config, true, true).0.limits.is_some());
+        assert!(
+            resolve(&["--no-sandbox"], &config, true, true)
+                .0
+                .limits
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn missing_tools_are_reported_as_warnings() {
+        let mut config = Config::default();
+        config.eval.timeout = Some(60);
+        let (sandbox, warnings) = resolve(&["--sandbox"], &config, false, false);
+        assert!(!sandbox.bwrap);
+        assert!(sandbox.limits.is_some());
+        assert_eq!(sandbox.timeout, None);
+        assert_eq!(warnings.len(), 2);
+
+        let (sandbox, warnings) = resolve(&["--timeout", "5"], &config, false, true);
+        assert_eq!(sandbox.timeout, Some(5));
+        assert!(warnings.is_empty());
+    }
+}
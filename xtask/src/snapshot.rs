@@ -0,0 +1,261 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::files::{copy_dir, course_files};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::localize_text;
+use crate::t;
+
+/// 快照保存位置，不放在 target 下以免被 cargo clean 清掉
+const SNAPSHOTS_DIR: &str = ".xtask/snapshots";
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// 课程名称
+    course: String,
+    /// 快照名称，不传则使用当前时间
+    name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// 课程名称
+    course: String,
+    /// 要恢复的快照名称
+    name: String,
+}
+
+#[derive(Args)]
+pub struct SnapshotsArgs {
+    /// 课程名称，不传则列出所有课程的快照
+    course: Option<String>,
+}
+
+/// 快照元数据，保存在快照目录的 snapshot.json 中
+#[derive(Serialize, Deserialize, Debug)]
+struct SnapshotMeta {
+    course: String,
+    name: String,
+    created_at: String,
+    files: usize,
+}
+
+impl SnapshotArgs {
+    pub fn snapshot(self) {
+        if let Err(e) = self.run_snapshot() {
+            eprintln!(
+                "{} {}",
+                t!("创建快照失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_snapshot(&self) -> Result<()> {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => unused_name(&self.course, &timestamp())?,
+        };
+        let meta = create_snapshot(&self.course, &name)?;
+        println!(
+            "{}",
+            t!(
+                "{} {} ({} 个文件)",
+                t!("已创建快照:").green().bold(),
+                meta.name,
+                meta.files
+            )
+        );
+        println!(
+            "{} cargo xtask restore {} {}",
+            t!("恢复命令:").blue(),
+            meta.course,
+            meta.name
+        );
+        Ok(())
+    }
+}
+
+impl RestoreArgs {
+    pub fn restore(self) {
+        if let Err(e) = self.run_restore() {
+            eprintln!(
+                "{} {}",
+                t!("恢复快照失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_restore(&self) -> Result<()> {
+        let course_dir = course_dir(&self.course)?;
+        let snapshot_dir = snapshot_dir(&self.course, &self.name)?;
+        if !snapshot_dir.exists() {
+            bail!(t!("找不到课程 {} 的快照 {}", self.course, self.name));
+        }
+
+        // 恢复前先保存当前状态，避免误操作无法挽回
+        let backup = unused_name(&self.course, &format!("{}-before-restore", timestamp()))?;
+        create_snapshot(&self.course, &backup)?;
+        println!("{} {}", t!("已备份当前状态为快照:").yellow().bold(), backup);
+
+        let files_dir = snapshot_dir.join("files");
+        let saved: BTreeSet<PathBuf> = course_files(&files_dir)?.into_iter().collect();
+        // 删除文件后变空的目录，由深到浅排列
+        let mut emptied = BTreeSet::new();
+        for relative in course_files(&course_dir)? {
+            if !saved.contains(&relative) {
+                fs::remove_file(course_dir.join(&relative))
+                    .context(t!("删除文件 {} 失败", relative.display()))?;
+                emptied.extend(relative.ancestors().skip(1).map(Path::to_path_buf));
+            }
+        }
+        for relative in emptied.iter().rev() {
+            let dir = course_dir.join(relative);
+            if !relative.as_os_str().is_empty() && is_empty_dir(&dir) {
+                fs::remove_dir(&dir).context(t!("删除目录 {} 失败", dir.display()))?;
+            }
+        }
+        copy_dir(&files_dir, &course_dir)?;
+
+        println!(
+            "{} {} -> {}",
+            t!("已恢复快照:").green().bold(),
+            self.name,
+            course_dir.display()
+        );
+        Ok(())
+    }
+}
+
+impl SnapshotsArgs {
+    pub fn snapshots(self) {
+        if let Err(e) = self.run_snapshots() {
+            eprintln!(
+                "{} {}",
+                t!("读取快照失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_snapshots(&self) -> Result<()> {
+        let root = Path::new(SNAPSHOTS_DIR);
+        let courses = match &self.course {
+            Some(course) => vec![course.clone()],
+            None => sorted_dir_names(root)?,
+        };
+
+        let mut found = false;
+        for course in courses {
+            let mut metas = Vec::new();
+            for name in sorted_dir_names(&root.join(&course))? {
+                let meta_path = root.join(&course).join(&name).join("snapshot.json");
+                let content = fs::read_to_string(&meta_path)
+                    .context(t!("读取 {} 失败", meta_path.display()))?;
+                metas.push(serde_json::from_str::<SnapshotMeta>(&content)?);
+            }
+            if metas.is_empty() {
+                continue;
+            }
+            found = true;
+            println!("{} {}", t!("课程:").blue().bold(), course);
+            for meta in metas {
+                println!(
+                    "{}",
+                    t!(
+                        "  {}  {}  {} 个文件",
+                        meta.name.green(),
+                        meta.created_at,
+                        meta.files
+                    )
+                );
+            }
+        }
+        if !found {
+            println!("{}", t!("还没有任何快照").yellow());
+        }
+        Ok(())
+    }
+}
+
+/// 把课程当前的文件保存为快照
+fn create_snapshot(course: &str, name: &str) -> Result<SnapshotMeta> {
+    let course_dir = course_dir(course)?;
+    let snapshot_dir = snapshot_dir(course, name)?;
+    if snapshot_dir.exists() {
+        bail!(t!("快照 {} 已存在", name));
+    }
+
+    let files_dir = snapshot_dir.join("files");
+    fs::create_dir_all(&files_dir).context(t!("创建目录 {} 失败", files_dir.display()))?;
+    copy_dir(&course_dir, &files_dir)?;
+
+    let meta = SnapshotMeta {
+        course: course.to_string(),
+        name: name.to_string(),
+        created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        files: course_files(&files_dir)?.len(),
+    };
+    fs::write(
+        snapshot_dir.join("snapshot.json"),
+        serde_json::to_string_pretty(&meta)?,
+    )
+    .context(t!("写入快照信息失败"))?;
+    Ok(meta)
+}
+
+/// 默认的快照名称，精确到毫秒
+fn timestamp() -> String {
+    chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+/// 同名快照已存在时依次加上 -2、-3 等后缀
+fn unused_name(course: &str, base: &str) -> Result<String> {
+    let mut name = base.to_string();
+    let mut counter = 1;
+    while snapshot_dir(course, &name)?.exists() {
+        counter += 1;
+        name = format!("{}-{}", base, counter);
+    }
+    Ok(name)
+}
+
+fn is_empty_dir(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+fn course_dir(course: &str) -> Result<PathBuf> {
+    let course_dir = Path::new("exercises").join(course);
+    if !course_dir.is_dir() {
+        bail!(t!("找不到课程目录: {}", course_dir.display()));
+    }
+    Ok(course_dir)
+}
+
+/// 快照名称会作为目录名，禁止路径分隔符和 `..`
+fn snapshot_dir(course: &str, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        bail!(t!("无效的快照名称: {}", name));
+    }
+    Ok(Path::new(SNAPSHOTS_DIR).join(course).join(name))
+}
+
+fn sorted_dir_names(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir).context(t!("无法读取目录: {}", dir.display()))? {
+        let entry = entry.context(t!("读取目录项失败"))?;
+        if entry.path().is_dir() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
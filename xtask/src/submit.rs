@@ -0,0 +1,180 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::eval::GradeResult;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::i18n::localize_text;
+use crate::t;
+
+#[derive(Args)]
+pub struct SubmitArgs {
+    /// 评测结果文件，默认取 xtask.toml 中的设置，否则为 eval_result.json
+    #[clap(short, long)]
+    result: Option<PathBuf>,
+
+    /// 接收评测结果的地址，默认取 xtask.toml 中的 submit.endpoint
+    #[clap(long)]
+    endpoint: Option<String>,
+
+    /// 只输出要提交的内容，不发送
+    #[clap(long)]
+    dry_run: bool,
+}
+
+impl SubmitArgs {
+    pub fn submit(self) {
+        if let Err(e) = self.run_submit() {
+            eprintln!(
+                "{} {}",
+                t!("提交失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_submit(&self) -> Result<()> {
+        let config = Config::load()?;
+        let result_path = self.result.as_ref().unwrap_or(&config.eval.result);
+        let content =
+            fs::read_to_string(result_path).context(t!("读取 {} 失败", result_path.display()))?;
+        let result: GradeResult =
+            serde_json::from_str(&content).context(t!("解析 {} 失败", result_path.display()))?;
+
+        let (payload, unmapped) = submission(&result, &config.courses)?;
+        for course in &unmapped {
+            println!(
+                "{}",
+                t!(
+                    "{} 课程 {} 没有在 xtask.toml 的 [courses] 中配置课程 ID",
+                    t!("警告:").yellow().bold(),
+                    course
+                )
+            );
+        }
+        let payload = serde_json::to_string_pretty(&payload).context(t!("序列化评测结果失败"))?;
+        if self.dry_run {
+            println!("{}", payload);
+            return Ok(());
+        }
+
+        let Some(endpoint) = self.endpoint.as_ref().or(config.submit.endpoint.as_ref()) else {
+            bail!(t!(
+                "没有提交地址，请在 xtask.toml 的 [submit] 中设置 endpoint 或使用 --endpoint"
+            ));
+        };
+        post(endpoint, &payload)?;
+        println!("{} {}", t!("评测结果已提交到").green().bold(), endpoint);
+        Ok(())
+    }
+}
+
+/// 要提交的内容：评测结果中每个练习加上所属课程在提交平台上的 `course_id`，
+/// 同时返回没有配置课程 ID 的课程
+fn submission(
+    result: &GradeResult,
+    courses: &BTreeMap<String, String>,
+) -> Result<(Value, Vec<String>)> {
+    let mut payload = serde_json::to_value(result).context(t!("序列化评测结果失败"))?;
+    let mut unmapped = Vec::new();
+    let exercises = payload["exercises"].as_array_mut().into_iter().flatten();
+    for (exercise, result) in exercises.zip(&result.exercises) {
+        match courses.get(&result.course) {
+            Some(id) => exercise["course_id"] = Value::from(id.as_str()),
+            None => {
+                if !result.course.is_empty() && !unmapped.contains(&result.course) {
+                    unmapped.push(result.course.clone());
+                }
+            }
+        }
+    }
+    Ok((payload, unmapped))
+}
+
+/// 用 curl 以 JSON 格式 POST 到提交地址，服务器返回错误状态时失败
+fn post(endpoint: &str, payload: &str) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(endpoint)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(t!("运行 curl 失败，请确认已安装 curl"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(payload.as_bytes())
+            .context(t!("运行 curl 失败，请确认已安装 curl"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .context(t!("运行 curl 失败，请确认已安装 curl"))?;
+    if !output.status.success() {
+        bail!(
+            "{}\n{}",
+            t!("提交到 {} 失败", endpoint),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grader::eval::ExerciseResult;
+
+    fn exercise(course: &str, name: &str) -> ExerciseResult {
+        ExerciseResult {
+            name: name.to_string(),
+            course: course.to_string(),
+            result: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exercises_carry_the_configured_course_id() {
+        let result = GradeResult::new(
+            vec![
+                exercise("rustlings", "intro1"),
+                exercise("learning-cxx", "exercise00"),
+                exercise("learning-cxx", "exercise01"),
+                exercise("", "legacy"),
+            ],
+            3,
+        );
+        let courses = BTreeMap::from([("rustlings".to_string(), "1885".to_string())]);
+        let (payload, unmapped) = submission(&result, &courses).unwrap();
+
+        let ids: Vec<&Value> = payload["exercises"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|exercise| &exercise["course_id"])
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                &Value::from("1885"),
+                &Value::Null,
+                &Value::Null,
+                &Value::Null
+            ]
+        );
+        assert_eq!(unmapped, ["learning-cxx"]);
+        assert_eq!(payload["statistics"]["total_exercations"], 4);
+    }
+}
@@ -0,0 +1,284 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::git::{git, git_ok, lines};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::i18n::localize_text;
+use crate::t;
+
+#[derive(Args)]
+pub struct SyncArgs {
+    /// 课程名称
+    course: String,
+    /// 课程模板仓库地址，首次同步时必须提供，会被添加为 upstream 远程仓库
+    #[clap(long)]
+    upstream: Option<String>,
+    /// 模板仓库的分支，默认依次尝试 master 和 main
+    #[clap(long)]
+    branch: Option<String>,
+    /// 使用 rebase 而不是 merge 合入模板更新
+    #[clap(long)]
+    rebase: bool,
+}
+
+/// 模板更新中单个文件的处理结果
+enum FileChange {
+    Added,
+    Updated,
+    Removed,
+    /// 学员和模板都修改过，保留学员的版本
+    Kept,
+}
+
+impl SyncArgs {
+    pub fn sync(self) {
+        if let Err(e) = self.run_sync() {
+            eprintln!(
+                "{} {}",
+                t!("同步课程失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_sync(&self) -> Result<()> {
+        println!("{} {}", t!("开始同步课程模板:").blue().bold(), self.course);
+
+        let course_dir = Path::new("exercises").join(&self.course);
+        if !course_dir.exists() {
+            bail!(t!("找不到课程目录: {}", course_dir.display()));
+        }
+        if !git_ok(&course_dir, &["rev-parse", "--git-dir"])? {
+            bail!(t!(
+                "{} 不是 git 仓库，请先使用 learn 命令配置课程",
+                course_dir.display()
+            ));
+        }
+
+        let branch = git(&course_dir, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+        if branch == "HEAD" {
+            bail!(t!("课程仓库处于 detached HEAD 状态，请先切换到你的分支"));
+        }
+        if !git(&course_dir, &["status", "--porcelain"])?.is_empty() {
+            bail!(t!("课程目录有未提交的修改，请先提交后再同步"));
+        }
+
+        self.configure_upstream(&course_dir)?;
+
+        println!("{}", t!("拉取模板仓库更新...").blue().bold());
+        git(&course_dir, &["fetch", "upstream"]).context(t!("拉取 upstream 失败"))?;
+        let upstream_ref = self.upstream_ref(&course_dir)?;
+
+        if git_ok(
+            &course_dir,
+            &["merge-base", "--is-ancestor", &upstream_ref, "HEAD"],
+        )? {
+            println!(
+                "{} {}",
+                t!("已包含模板的所有更新:").green().bold(),
+                upstream_ref
+            );
+            return Ok(());
+        }
+
+        let base = git(&course_dir, &["merge-base", "HEAD", &upstream_ref])
+            .context(t!("找不到与模板仓库的共同祖先，无法同步"))?;
+        let learner_changed: BTreeSet<String> =
+            lines(&git(&course_dir, &["diff", "--name-only", &base, "HEAD"])?)
+                .into_iter()
+                .collect();
+
+        // 模板侧的改动: 学员改过的文件一律保留学员的版本
+        let mut changes = Vec::new();
+        for line in lines(&git(
+            &course_dir,
+            &[
+                "diff",
+                "--name-status",
+                "--no-renames",
+                &base,
+                &upstream_ref,
+            ],
+        )?) {
+            let Some((status, path)) = line.split_once('\t') else {
+                continue;
+            };
+            let change = if learner_changed.contains(path) {
+                FileChange::Kept
+            } else {
+                match status {
+                    "A" => FileChange::Added,
+                    "D" => FileChange::Removed,
+                    _ => FileChange::Updated,
+                }
+            };
+            changes.push((path.to_string(), change));
+        }
+        let kept: Vec<&str> = changes
+            .iter()
+            .filter(|(_, change)| matches!(change, FileChange::Kept))
+            .map(|(path, _)| path.as_str())
+            .collect();
+
+        if self.rebase {
+            self.rebase_onto(&course_dir, &upstream_ref, &kept)?;
+        } else {
+            self.merge_from(&course_dir, &upstream_ref, &kept)?;
+        }
+
+        println!("{}", t!("同步结果:").green().bold());
+        for (path, change) in &changes {
+            match change {
+                FileChange::Added => println!("  {} {}", t!("新增").green(), path),
+                FileChange::Updated => println!("  {} {}", t!("更新").blue(), path),
+                FileChange::Removed => println!("  {} {}", t!("删除").yellow(), path),
+                FileChange::Kept => println!("  {} {}", t!("保留你的版本").yellow(), path),
+            }
+        }
+        println!("{} {}", t!("成功同步课程:").green().bold(), self.course);
+        println!(
+            "{}",
+            t!("请检查结果后推送到你的 fork，并在仓库根目录提交子模块的更新").blue()
+        );
+        Ok(())
+    }
+
+    /// 添加或更新 upstream 远程仓库
+    fn configure_upstream(&self, course_dir: &Path) -> Result<()> {
+        let current = git(course_dir, &["remote", "get-url", "upstream"]).ok();
+        match (&self.upstream, current) {
+            (Some(url), None) => {
+                println!("{} {}", t!("添加 upstream 远程仓库:").blue().bold(), url);
+                git(course_dir, &["remote", "add", "upstream", url])?;
+            }
+            (Some(url), Some(current)) if *url != current => {
+                println!(
+                    "{} {} -> {}",
+                    t!("更新 upstream 地址:").yellow().bold(),
+                    current,
+                    url
+                );
+                git(course_dir, &["remote", "set-url", "upstream", url])?;
+            }
+            (_, Some(_)) => {}
+            (None, None) => {
+                bail!(t!(
+                    "未配置 upstream 远程仓库，请使用 --upstream 指定课程模板仓库地址"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// 确定要合入的模板分支
+    fn upstream_ref(&self, course_dir: &Path) -> Result<String> {
+        let candidates = match &self.branch {
+            Some(branch) => vec![branch.as_str()],
+            None => vec!["master", "main"],
+        };
+        for branch in candidates {
+            let upstream_ref = format!("upstream/{}", branch);
+            if git_ok(
+                course_dir,
+                &["rev-parse", "--verify", "--quiet", &upstream_ref],
+            )? {
+                return Ok(upstream_ref);
+            }
+        }
+        bail!(t!("upstream 中找不到可用的分支，请使用 --branch 指定"))
+    }
+
+    /// 以 merge 方式合入模板更新，冲突文件保留学员的版本
+    fn merge_from(&self, course_dir: &Path, upstream_ref: &str, kept: &[&str]) -> Result<()> {
+        println!("{} {}", t!("合并模板更新:").blue().bold(), upstream_ref);
+        // 有冲突时 merge 会返回失败，冲突交给下面统一处理；没有冲突却失败时（例如本地修改会被覆盖）直接撤销
+        let merged = git(
+            course_dir,
+            &["merge", "--no-ff", "--no-commit", upstream_ref],
+        );
+        if let Err(e) = merged
+            && lines(&git(
+                course_dir,
+                &["diff", "--name-only", "--diff-filter=U"],
+            )?)
+            .is_empty()
+        {
+            git_ok(course_dir, &["merge", "--abort"])?;
+            bail!(t!("合并模板更新失败，已撤销本次合并: {}", e));
+        }
+
+        for path in kept {
+            restore_path(course_dir, "HEAD", path)?;
+        }
+
+        let conflicts = lines(&git(
+            course_dir,
+            &["diff", "--name-only", "--diff-filter=U"],
+        )?);
+        if !conflicts.is_empty() {
+            git_ok(course_dir, &["merge", "--abort"])?;
+            report_conflicts(&conflicts);
+            bail!(t!("合并模板更新时出现无法自动解决的冲突，已撤销本次合并"));
+        }
+
+        let message = format!("Merge {} into learner branch", upstream_ref);
+        git(course_dir, &["commit", "--no-verify", "-m", &message])?;
+        Ok(())
+    }
+
+    /// 以 rebase 方式把学员的提交移到模板之上，之后恢复学员修改过的文件
+    fn rebase_onto(&self, course_dir: &Path, upstream_ref: &str, kept: &[&str]) -> Result<()> {
+        println!("{} {}", t!("变基到模板更新:").blue().bold(), upstream_ref);
+        let original_head = git(course_dir, &["rev-parse", "HEAD"])?;
+
+        // rebase 中 theirs 指正在重放的学员提交
+        if !git_ok(course_dir, &["rebase", "-X", "theirs", upstream_ref])? {
+            let conflicts = lines(&git(
+                course_dir,
+                &["diff", "--name-only", "--diff-filter=U"],
+            )?);
+            git_ok(course_dir, &["rebase", "--abort"])?;
+            report_conflicts(&conflicts);
+            bail!(t!("变基时出现无法自动解决的冲突，已撤销本次变基"));
+        }
+
+        for path in kept {
+            restore_path(course_dir, &original_head, path)?;
+        }
+        if !git(course_dir, &["status", "--porcelain"])?.is_empty() {
+            git(
+                course_dir,
+                &[
+                    "commit",
+                    "--no-verify",
+                    "-m",
+                    "Keep learner versions of modified exercises",
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 把文件恢复为指定提交中的版本，该提交中不存在的文件则删除
+fn restore_path(course_dir: &Path, rev: &str, path: &str) -> Result<()> {
+    let object = format!("{}:{}", rev, path);
+    if git_ok(course_dir, &["cat-file", "-e", &object])? {
+        git(course_dir, &["checkout", rev, "--", path])?;
+    } else {
+        git(
+            course_dir,
+            &["rm", "-q", "-f", "--ignore-unmatch", "--", path],
+        )?;
+    }
+    Ok(())
+}
+
+fn report_conflicts(conflicts: &[String]) {
+    println!("{}", t!("冲突的练习文件:").red().bold());
+    for path in conflicts {
+        println!("  {} {}", "✗".red().bold(), path);
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use colored::*;
+use grader::tamper::write_checksums;
+use std::path::Path;
+
+use crate::i18n::localize_text;
+use crate::t;
+
+#[derive(Args)]
+pub struct ChecksumsArgs {
+    /// 课程名称，应在未修改过的课程模板上运行，生成的清单需提交到模板仓库
+    course: String,
+}
+
+impl ChecksumsArgs {
+    pub fn checksums(self) {
+        if let Err(e) = self.run_checksums() {
+            eprintln!(
+                "{} {}",
+                t!("生成校验和失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_checksums(&self) -> Result<()> {
+        let course_path = Path::new("exercises").join(&self.course);
+        if !course_path.is_dir() {
+            bail!(t!("找不到课程目录: {}", course_path.display()));
+        }
+
+        let (path, count) = write_checksums(&course_path)?;
+        println!(
+            "{}",
+            t!(
+                "{} {} ({} 个文件)",
+                t!("已生成测试代码校验和:").green().bold(),
+                path.display(),
+                count
+            )
+        );
+        Ok(())
+    }
+}
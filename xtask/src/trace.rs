@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tracing::Subscriber;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::time::ChronoLocal;
+use tracing_subscriber::prelude::*;
+
+use crate::t;
+
+/// 命令行中 -v 的个数，-q 时为 -1
+static VERBOSITY: OnceLock<i8> = OnceLock::new();
+
+/// 按命令行参数设置 xtask 自身的运行日志，与学员代码的评测日志（见 grader::logs）无关
+///
+/// 默认只输出警告和错误，-v 输出各阶段的耗时，-vv 输出调试信息，-q 只输出错误。
+/// 日志文件打不开时仍然输出到终端，并返回错误。
+pub fn init(verbose: u8, quiet: bool, file: Option<&Path>) -> Result<()> {
+    let verbosity = if quiet {
+        -1
+    } else {
+        verbose.min(i8::MAX as u8) as i8
+    };
+    let _ = VERBOSITY.set(verbosity);
+
+    let file = file
+        .map(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .context(t!("打开日志文件 {} 失败", path.display()))
+        })
+        .transpose();
+    let (file, result) = match file {
+        Ok(file) => (file, Ok(())),
+        Err(e) => (None, Err(e)),
+    };
+    let ansi = colored::control::SHOULD_COLORIZE.should_colorize();
+    let _ = tracing::subscriber::set_global_default(subscriber(
+        std::io::stderr,
+        stderr_level(verbosity),
+        ansi,
+        file.map(Mutex::<File>::new),
+    ));
+    result
+}
+
+/// 是否指定了 -v，评测时同时显示学员程序的完整输出
+pub fn verbose() -> bool {
+    VERBOSITY.get().is_some_and(|verbosity| *verbosity > 0)
+}
+
+/// 是否指定了 -q，评测时只输出评测结果统计
+pub fn quiet() -> bool {
+    VERBOSITY.get().is_some_and(|verbosity| *verbosity < 0)
+}
+
+/// 输出到终端的最详细级别
+fn stderr_level(verbosity: i8) -> LevelFilter {
+    match verbosity {
+        ..0 => LevelFilter::ERROR,
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// 终端输出带有时间、级别和所在的阶段；日志文件为 JSON Lines 格式，记录所有级别
+///
+/// 阶段结束时各输出一条 `close` 记录，带有阶段的耗时 `time.busy` 和 `time.idle`。
+fn subscriber<T, F>(
+    terminal: T,
+    level: LevelFilter,
+    ansi: bool,
+    file: Option<F>,
+) -> impl Subscriber + Send + Sync
+where
+    T: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    F: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let terminal = tracing_subscriber::fmt::layer()
+        .with_writer(terminal)
+        .with_ansi(ansi)
+        .with_target(false)
+        .with_timer(ChronoLocal::new("%H:%M:%S%.3f".to_string()))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_filter(level);
+    let file = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(file)
+            .with_timer(ChronoLocal::rfc_3339())
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_filter(LevelFilter::TRACE)
+    });
+    tracing_subscriber::registry().with(terminal).with(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::io;
+    use std::sync::Arc;
+    use tracing::{debug, debug_span, info_span, warn};
+
+    /// 收集输出的内存缓冲区
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'w> MakeWriter<'w> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'w self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// 在课程和练习阶段中各记录一条日志
+    fn grade(terminal: &Buffer, level: LevelFilter, file: Option<&Buffer>) {
+        let subscriber = subscriber(terminal.clone(), level, false, file.cloned());
+        tracing::subscriber::with_default(subscriber, || {
+            let _course = info_span!("course", course = "rustlings").entered();
+            {
+                let _exercise = info_span!("exercise", exercise = "intro1").entered();
+                let _parse = debug_span!("parse").entered();
+                debug!(count = 2, "找到练习");
+            }
+            warn!(path = "exercises/missing", "找不到课程目录");
+        });
+    }
+
+    #[test]
+    fn verbosity_selects_the_terminal_level() {
+        assert_eq!(stderr_level(-1), LevelFilter::ERROR);
+        assert_eq!(stderr_level(0), LevelFilter::WARN);
+        assert_eq!(stderr_level(1), LevelFilter::INFO);
+        assert_eq!(stderr_level(2), LevelFilter::DEBUG);
+        assert_eq!(stderr_level(5), LevelFilter::TRACE);
+    }
+
+    #[test]
+    fn terminal_output_is_filtered_by_level() {
+        let terminal = Buffer::default();
+        grade(&terminal, LevelFilter::WARN, None);
+        let text = terminal.text();
+        assert_eq!(text.lines().count(), 1, "{}", text);
+        // 低于终端级别的阶段不显示
+        assert!(
+            text.contains(" WARN 找不到课程目录 path=\"exercises/missing\""),
+            "{}",
+            text
+        );
+
+        // -v 时还有练习和课程阶段结束的耗时
+        let terminal = Buffer::default();
+        grade(&terminal, LevelFilter::INFO, None);
+        let text = terminal.text();
+        assert!(!text.contains("找到练习"), "{}", text);
+        assert!(text.contains("course{course=\"rustlings\"}:exercise{exercise=\"intro1\"}: close"));
+        assert!(text.contains("time.busy="));
+    }
+
+    #[test]
+    fn file_records_every_level_as_json() {
+        let terminal = Buffer::default();
+        let file = Buffer::default();
+        grade(&terminal, LevelFilter::ERROR, Some(&file));
+        assert_eq!(terminal.text(), "");
+
+        let records: Vec<Value> = file
+            .text()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let found = records
+            .iter()
+            .find(|record| record["fields"]["message"] == "找到练习")
+            .unwrap();
+        assert_eq!(found["level"], "DEBUG");
+        assert_eq!(found["fields"]["count"], 2);
+        assert!(found["timestamp"].is_string());
+        assert_eq!(
+            found["spans"],
+            serde_json::json!([
+                {"name": "course", "course": "rustlings"},
+                {"name": "exercise", "exercise": "intro1"},
+                {"name": "parse"},
+            ])
+        );
+
+        // 每个阶段结束时有一条带耗时的记录，消息不随界面语言变化
+        let closed: Vec<&Value> = records
+            .iter()
+            .filter(|record| record["fields"]["message"] == "close")
+            .collect();
+        assert_eq!(closed.len(), 3);
+        assert_eq!(closed[0]["span"]["name"], "parse");
+        assert_eq!(
+            closed[2]["span"],
+            serde_json::json!({"name": "course", "course": "rustlings"})
+        );
+        assert!(closed[2]["fields"]["time.busy"].is_string());
+    }
+}
@@ -0,0 +1,308 @@
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use colored::*;
+use grader::course::{Unit, course_units};
+use grader::eval::Grader;
+use grader::report::{Event, Reporter};
+use notify_debouncer_full::notify::{EventKind, RecursiveMode};
+use notify_debouncer_full::{DebouncedEvent, new_debouncer};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::eval::GradeOptions;
+use crate::i18n::localize_text;
+use crate::report::Terminal;
+use crate::sandbox;
+use crate::t;
+
+/// 合并同一次保存产生的多个文件事件：编辑器保存时常常先写临时文件再改名
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// 课程名称
+    course: String,
+
+    #[clap(flatten)]
+    options: GradeOptions,
+}
+
+impl WatchArgs {
+    pub fn watch(self) {
+        if let Err(e) = self.run_watch() {
+            eprintln!(
+                "{} {}",
+                t!("监视模式失败:").red().bold(),
+                localize_text(&e.to_string())
+            );
+        }
+    }
+
+    fn run_watch(&self) -> Result<()> {
+        let course_path = Path::new("exercises").join(&self.course);
+        if !course_path.is_dir() {
+            bail!(t!("找不到课程目录: {}", course_path.display()));
+        }
+
+        let units = course_units(&self.course, &course_path)?;
+        if units.is_empty() {
+            bail!(t!("课程 {} 中没有找到练习", self.course));
+        }
+        println!(
+            "{}",
+            t!(
+                "{} {} ({} 个练习)",
+                t!("进入监视模式:").blue().bold(),
+                self.course,
+                units.len()
+            )
+        );
+
+        let workspace_root = std::env::current_dir().context(t!("无法获取当前工作目录"))?;
+        let (grader, warnings) = self.options.grader(&Config::load()?, &workspace_root);
+        sandbox::report(&grader.sandbox, &warnings);
+
+        // 先开始监视，评测第一个练习期间保存的修改也不会漏掉
+        let course_root = fs::canonicalize(&course_path)
+            .context(t!("无法读取目录: {}", course_path.display()))?;
+        let (sender, events) = mpsc::channel();
+        let mut debouncer =
+            new_debouncer(DEBOUNCE, None, sender).context(t!("无法监视课程目录"))?;
+        debouncer
+            .watch(&course_root, RecursiveMode::Recursive)
+            .context(t!("无法监视课程目录"))?;
+
+        // None 表示还没有评测过
+        let mut solved: Vec<Option<bool>> = vec![None; units.len()];
+        let mut current = self.advance(&course_path, &units, &mut solved, 0, &grader);
+
+        for result in events {
+            let changed = match result {
+                Ok(events) => modified_paths(&events),
+                Err(errors) => {
+                    for e in errors {
+                        eprintln!(
+                            "{} {}",
+                            t!("警告:").yellow().bold(),
+                            t!("监视课程目录出错: {}", e)
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            for index in touched_units(&units, &course_path, &course_root, &changed) {
+                println!();
+                // 评测出错时保留原来的状态，等下次保存再评测
+                let Some(passed) = self.grade(&units[index], &course_path, &grader) else {
+                    continue;
+                };
+                solved[index] = Some(passed);
+                if passed && current == Some(index) {
+                    current = self.advance(&course_path, &units, &mut solved, index + 1, &grader);
+                } else if !passed && current.is_none_or(|current| index < current) {
+                    // 之前通过的练习又被改坏了，回到这里
+                    current = Some(index);
+                    print_current(&units[index]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 评测并总是显示编译器和测试输出；评测出错时显示错误并返回 None，不退出监视模式
+    fn grade(&self, unit: &Unit, course_path: &Path, grader: &Grader) -> Option<bool> {
+        let terminal = Terminal::new(true);
+        let result = unit.grade(grader, &self.course, course_path, &Regrade(&terminal));
+        terminal.finish();
+        match result {
+            Ok(result) => Some(result.result),
+            Err(e) => {
+                eprintln!(
+                    "{} {}",
+                    t!("评测 {} 出错:", unit.name()).red().bold(),
+                    localize_text(&e.to_string())
+                );
+                None
+            }
+        }
+    }
+
+    /// 从 `from` 开始依次评测，停在第一个未通过的练习上
+    fn advance(
+        &self,
+        course_path: &Path,
+        units: &[Unit],
+        solved: &mut [Option<bool>],
+        from: usize,
+        grader: &Grader,
+    ) -> Option<usize> {
+        for index in from..units.len() {
+            if solved[index] == Some(true) {
+                continue;
+            }
+            // 评测出错的练习也停在这里，修改后重新评测
+            let passed = self.grade(&units[index], course_path, grader);
+            solved[index] = passed;
+            if passed != Some(true) {
+                print_current(&units[index]);
+                return Some(index);
+            }
+        }
+
+        let done = solved.iter().filter(|s| **s == Some(true)).count();
+        println!();
+        println!(
+            "{} {}/{}",
+            t!("恭喜，所有练习都已通过!").green().bold(),
+            done,
+            units.len()
+        );
+        None
+    }
+}
+
+/// 逐个评测练习时不重复显示课程的评测方式和练习数
+struct Regrade<'a>(&'a Terminal);
+
+impl Reporter for Regrade<'_> {
+    fn report(&self, event: Event) {
+        if !matches!(event, Event::Course(_) | Event::Found(_)) {
+            self.0.report(event);
+        }
+    }
+}
+
+fn print_current(unit: &Unit) {
+    println!();
+    println!(
+        "{} {} ({})",
+        t!("当前练习:").yellow().bold(),
+        unit.name(),
+        unit.location().display()
+    );
+    println!("{}", t!("保存文件后会自动重新评测，按 Ctrl+C 退出").blue());
+}
+
+/// 内容或目录项有变化的路径；评测时编译器读取练习文件也会产生打开和关闭的事件，忽略这些事件
+fn modified_paths(events: &[DebouncedEvent]) -> Vec<PathBuf> {
+    events
+        .iter()
+        .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+        .flat_map(|event| event.paths.iter().cloned())
+        .collect()
+}
+
+/// 变化的文件涉及的练习，按课程顺序排列
+///
+/// `changed` 是监视到的绝对路径，位于 `course_root`（课程目录的绝对路径）下；练习中的路径
+/// 以 `course_path` 开头，先换算过去再比较。target 和 .git 下的变化是构建和 git 产生的，忽略。
+fn touched_units(
+    units: &[Unit],
+    course_path: &Path,
+    course_root: &Path,
+    changed: &[PathBuf],
+) -> Vec<usize> {
+    let changed: Vec<PathBuf> = changed
+        .iter()
+        .filter_map(|path| path.strip_prefix(course_root).ok())
+        .filter(|relative| {
+            !relative
+                .components()
+                .any(|part| part.as_os_str() == "target" || part.as_os_str() == ".git")
+        })
+        .map(|relative| course_path.join(relative))
+        .collect();
+    (0..units.len())
+        .filter(|&i| changed.iter().any(|path| units[i].contains(path)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grader::course::CxxExercise;
+
+    #[test]
+    fn reading_files_is_not_a_change() {
+        use notify_debouncer_full::notify::Event;
+        use notify_debouncer_full::notify::event::{
+            AccessKind, AccessMode, ModifyKind, RemoveKind,
+        };
+        use std::time::Instant;
+
+        let event = |kind, path: &str| {
+            DebouncedEvent::new(
+                Event::new(kind).add_path(PathBuf::from(path)),
+                Instant::now(),
+            )
+        };
+        let events = [
+            event(
+                EventKind::Access(AccessKind::Open(AccessMode::Read)),
+                "/c/a.rs",
+            ),
+            event(
+                EventKind::Access(AccessKind::Close(AccessMode::Read)),
+                "/c/a.rs",
+            ),
+            event(EventKind::Modify(ModifyKind::Any), "/c/b.rs"),
+            event(
+                EventKind::Access(AccessKind::Close(AccessMode::Write)),
+                "/c/c.rs",
+            ),
+            event(EventKind::Remove(RemoveKind::File), "/c/d.rs"),
+        ];
+        assert_eq!(
+            modified_paths(&events),
+            [PathBuf::from("/c/b.rs"), PathBuf::from("/c/d.rs")]
+        );
+    }
+
+    #[test]
+    fn changed_paths_map_to_exercises() {
+        let course_path = Path::new("exercises/learning-cxx");
+        let root = Path::new("/work/exercises/learning-cxx");
+        let units = [
+            Unit::Cxx(CxxExercise {
+                name: "exercise00".to_string(),
+                dir: course_path.join("exercises/00_hello_world"),
+            }),
+            Unit::Cxx(CxxExercise {
+                name: "exercise01".to_string(),
+                dir: course_path.join("exercises/01_variable"),
+            }),
+        ];
+        let touched = |paths: &[&str]| {
+            let changed: Vec<PathBuf> = paths.iter().map(|path| root.join(path)).collect();
+            touched_units(&units, course_path, root, &changed)
+        };
+
+        assert_eq!(touched(&["exercises/01_variable/main.cpp"]), [1]);
+        assert_eq!(
+            touched(&[
+                "exercises/01_variable/main.cpp",
+                "exercises/00_hello_world/main.cpp",
+                "exercises/00_hello_world/main.cpp",
+            ]),
+            [0, 1]
+        );
+        assert!(touched(&["README.md"]).is_empty());
+    }
+
+    #[test]
+    fn build_output_and_paths_outside_the_course_are_ignored() {
+        let course_path = Path::new("exercises/rustlings");
+        let root = Path::new("/work/exercises/rustlings");
+        let units = [Unit::Rust(course_path.join("exercises/intro/intro1.rs"))];
+        let touched = |changed: &[PathBuf]| touched_units(&units, course_path, root, changed);
+
+        assert_eq!(touched(&[root.join("exercises/intro/intro1.rs")]), [0]);
+        assert!(touched(&[root.join("target/exercises/intro/intro1.rs")]).is_empty());
+        assert!(touched(&[root.join(".git/exercises/intro/intro1.rs")]).is_empty());
+        assert!(touched(&[PathBuf::from("/other/exercises/intro/intro1.rs")]).is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+//! `cargo xtask eval` 的集成测试：xtask.toml 中配置的课程有的无法评测
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn xtask(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xtask"))
+        .args(args)
+        .current_dir(root)
+        .env("XDG_CONFIG_HOME", root.join(".config"))
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .output()
+        .unwrap()
+}
+
+/// 配置了三个课程：demo 可以评测，missing 不存在，pending 是尚未检出的子模块
+fn setup() -> TempDir {
+    let root = TempDir::new().unwrap();
+    let demo = root.path().join("exercises/demo/exercises");
+    fs::create_dir_all(&demo).unwrap();
+    fs::write(demo.join("intro1.rs"), "fn main() {}\n").unwrap();
+    fs::create_dir_all(root.path().join("exercises/pending")).unwrap();
+    fs::write(
+        root.path().join(".gitmodules"),
+        "[submodule \"pending\"]\n\tpath = exercises/pending\n\turl = https://example.com/pending.git\n",
+    )
+    .unwrap();
+    fs::write(
+        root.path().join("xtask.toml"),
+        "[eval]\ncourses = [\"missing\", \"pending\", \"demo\"]\n",
+    )
+    .unwrap();
+    root
+}
+
+#[test]
+fn courses_that_cannot_be_graded_are_skipped() {
+    let root = setup();
+    let output = xtask(root.path(), &["eval"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("exercises/missing"), "{}", stdout);
+
+    let result = fs::read_to_string(root.path().join("eval_result.json")).unwrap();
+    let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+    let exercises = result["exercises"].as_array().unwrap();
+    assert_eq!(exercises.len(), 1, "{}", result);
+    assert_eq!(exercises[0]["course"], "demo");
+    assert_eq!(exercises[0]["result"], true);
+}
@@ -0,0 +1,108 @@
+//! `cargo xtask reset` 的集成测试：课程是带有模板历史的 git 仓库
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+const TEMPLATE: &str = "fn main() { todo!() }\n";
+
+fn isolated(command: &mut Command) -> &mut Command {
+    command
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_AUTHOR_NAME", "learner")
+        .env("GIT_AUTHOR_EMAIL", "learner@example.com")
+        .env("GIT_COMMITTER_NAME", "learner")
+        .env("GIT_COMMITTER_EMAIL", "learner@example.com")
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = isolated(Command::new("git").current_dir(dir).args(args))
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {} failed", args.join(" "));
+}
+
+/// 模板提交在 upstream/master 上，之后学员修改了练习，课程中还有同名的参考答案
+fn setup(name: &str) -> (TempDir, PathBuf) {
+    let root = TempDir::new().unwrap();
+    let course = root.path().join("exercises").join(name);
+    fs::create_dir_all(course.join("exercises")).unwrap();
+    fs::create_dir_all(course.join("solutions")).unwrap();
+    fs::write(course.join("exercises/variables1.rs"), TEMPLATE).unwrap();
+    fs::write(course.join("solutions/variables1.rs"), "fn main() {}\n").unwrap();
+    git(&course, &["init", "-q", "-b", "master"]);
+    git(&course, &["add", "-A"]);
+    git(&course, &["commit", "-q", "-m", "template"]);
+    git(&course, &["update-ref", "refs/remotes/upstream/master", "HEAD"]);
+    fs::write(course.join("exercises/variables1.rs"), "fn main() { 1; }\n").unwrap();
+    (root, course)
+}
+
+fn reset(root: &Path, course: &str, exercise: &str) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_xtask"));
+    command.args(["reset", course, exercise]).current_dir(root);
+    isolated(&mut command).output().unwrap()
+}
+
+#[test]
+fn name_only_matches_exercises_not_solutions() {
+    let (root, course) = setup("rustlings");
+
+    let output = reset(root.path(), "rustlings", "variables1");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(
+        fs::read_to_string(course.join("exercises/variables1.rs")).unwrap(),
+        TEMPLATE
+    );
+    assert_eq!(
+        fs::read_to_string(course.join("solutions/variables1.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+}
+
+#[test]
+fn ambiguous_name_lists_the_candidates() {
+    // 不是 rustlings 的课程与 eval 一样把所有 .rs 文件都当作练习
+    let (root, course) = setup("demo");
+
+    let output = reset(root.path(), "demo", "variables1");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exercises/variables1.rs"), "{}", stderr);
+    assert!(stderr.contains("solutions/variables1.rs"), "{}", stderr);
+    assert_eq!(
+        fs::read_to_string(course.join("exercises/variables1.rs")).unwrap(),
+        "fn main() { 1; }\n"
+    );
+}
+
+#[test]
+fn relative_path_still_selects_any_file() {
+    let (root, course) = setup("rustlings");
+    fs::write(course.join("solutions/variables1.rs"), "changed\n").unwrap();
+
+    let output = reset(root.path(), "rustlings", "solutions/variables1.rs");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(
+        fs::read_to_string(course.join("solutions/variables1.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+}
+
+#[test]
+fn unknown_template_fails_instead_of_using_head() {
+    let (root, course) = setup("rustlings");
+    git(&course, &["update-ref", "-d", "refs/remotes/upstream/master"]);
+    git(&course, &["commit", "-q", "-am", "learner work"]);
+
+    let output = reset(root.path(), "rustlings", "variables1");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("无法确定"), "{}", stderr);
+    assert_eq!(
+        fs::read_to_string(course.join("exercises/variables1.rs")).unwrap(),
+        "fn main() { 1; }\n"
+    );
+}
@@ -0,0 +1,77 @@
+//! `cargo xtask snapshot` 和 `cargo xtask restore` 的集成测试
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+const COURSE: &str = "demo";
+
+fn xtask(root: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_xtask"))
+        .args(args)
+        .current_dir(root)
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    output
+}
+
+fn course(root: &Path) -> PathBuf {
+    root.join("exercises").join(COURSE)
+}
+
+fn snapshot_names(root: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(root.join(".xtask/snapshots").join(COURSE))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn setup() -> TempDir {
+    let root = TempDir::new().unwrap();
+    let course = course(root.path());
+    fs::create_dir_all(course.join("exercises")).unwrap();
+    fs::write(course.join("exercises/intro1.rs"), "fn main() {}\n").unwrap();
+    root
+}
+
+#[test]
+fn restore_removes_new_files_and_their_empty_directories() {
+    let root = setup();
+    let course = course(root.path());
+    xtask(root.path(), &["snapshot", COURSE, "start"]);
+
+    fs::write(course.join("exercises/intro1.rs"), "fn main() { 1; }\n").unwrap();
+    fs::create_dir_all(course.join("exercises/extra/deep")).unwrap();
+    fs::write(course.join("exercises/extra/deep/new.rs"), "\n").unwrap();
+    xtask(root.path(), &["restore", COURSE, "start"]);
+
+    assert_eq!(
+        fs::read_to_string(course.join("exercises/intro1.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+    assert!(!course.join("exercises/extra").exists());
+    assert!(course.join("exercises").is_dir());
+}
+
+#[test]
+fn default_names_do_not_collide() {
+    let root = setup();
+    for _ in 0..3 {
+        xtask(root.path(), &["snapshot", COURSE]);
+    }
+    xtask(root.path(), &["restore", COURSE, &snapshot_names(root.path())[0]]);
+    xtask(root.path(), &["restore", COURSE, &snapshot_names(root.path())[0]]);
+
+    let names = snapshot_names(root.path());
+    assert_eq!(names.len(), 5, "{:?}", names);
+    assert_eq!(
+        names.iter().filter(|name| name.contains("-before-restore")).count(),
+        2
+    );
+}
@@ -0,0 +1,244 @@
+//! `cargo xtask sync` 在本地裸仓库上的集成测试：模板仓库是一个裸仓库，学员的课程是它的克隆
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+const COURSE: &str = "demo";
+
+/// 模板仓库、维护者提交模板更新用的工作区，以及 exercises 下学员的课程
+struct Fixture {
+    root: TempDir,
+    upstream: PathBuf,
+    template: PathBuf,
+    course: PathBuf,
+}
+
+/// 测试不受本机 git 配置和语言设置影响
+fn isolated(command: &mut Command) -> &mut Command {
+    command
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_AUTHOR_NAME", "learner")
+        .env("GIT_AUTHOR_EMAIL", "learner@example.com")
+        .env("GIT_COMMITTER_NAME", "learner")
+        .env("GIT_COMMITTER_EMAIL", "learner@example.com")
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = isolated(Command::new("git").current_dir(dir).args(args))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn write(dir: &Path, path: &str, content: &str) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn read(dir: &Path, path: &str) -> String {
+    fs::read_to_string(dir.join(path)).unwrap()
+}
+
+fn commit_all(dir: &Path, message: &str) {
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+impl Fixture {
+    /// 模板有 intro1.rs 和 intro2.rs，学员克隆后修改了 intro1.rs 并提交
+    fn new() -> Self {
+        let root = TempDir::new().unwrap();
+        let template = root.path().join("template");
+        let upstream = root.path().join("upstream.git");
+        let course = root.path().join("exercises").join(COURSE);
+
+        fs::create_dir_all(&template).unwrap();
+        git(&template, &["init", "-q", "-b", "master"]);
+        write(&template, "exercises/intro1.rs", "fn main() { todo!() }\n");
+        write(&template, "exercises/intro2.rs", "fn main() { todo!() }\n");
+        commit_all(&template, "template");
+        git(
+            root.path(),
+            &["clone", "-q", "--bare", "template", "upstream.git"],
+        );
+
+        fs::create_dir_all(course.parent().unwrap()).unwrap();
+        git(
+            root.path(),
+            &["clone", "-q", upstream.to_str().unwrap(), course.to_str().unwrap()],
+        );
+        write(&course, "exercises/intro1.rs", "fn main() { println!(\"done\") }\n");
+        commit_all(&course, "solve intro1");
+
+        Fixture {
+            root,
+            upstream,
+            template,
+            course,
+        }
+    }
+
+    /// 维护者提交模板更新并推送到模板仓库
+    fn publish(&self, change: impl FnOnce(&Path)) {
+        change(&self.template);
+        commit_all(&self.template, "update template");
+        git(
+            &self.template,
+            &["push", "-q", self.upstream.to_str().unwrap(), "master"],
+        );
+    }
+
+    fn sync(&self, extra: &[&str]) -> Output {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_xtask"));
+        command
+            .args(["sync", COURSE, "--upstream"])
+            .arg(&self.upstream)
+            .args(extra)
+            .current_dir(self.root.path());
+        isolated(&mut command).output().unwrap()
+    }
+
+    fn head(&self) -> String {
+        git(&self.course, &["rev-parse", "HEAD"])
+    }
+
+    fn upstream_head(&self) -> String {
+        git(&self.template, &["rev-parse", "HEAD"])
+    }
+
+    fn assert_clean(&self) {
+        assert_eq!(git(&self.course, &["status", "--porcelain"]), "");
+        let git_dir = self.course.join(".git");
+        assert!(!git_dir.join("MERGE_HEAD").exists());
+        assert!(!git_dir.join("rebase-merge").exists());
+        assert!(!git_dir.join("rebase-apply").exists());
+    }
+}
+
+/// 模板修改了学员做过的 intro1.rs、更新了 intro2.rs，并新增 intro3.rs
+fn typical_update(template: &Path) {
+    write(template, "exercises/intro1.rs", "fn main() { todo!(\"new hint\") }\n");
+    write(template, "exercises/intro2.rs", "fn main() { todo!(\"fixed\") }\n");
+    write(template, "exercises/intro3.rs", "fn main() { todo!() }\n");
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn merge_brings_template_updates_and_keeps_learner_files() {
+    let fixture = Fixture::new();
+    fixture.publish(typical_update);
+
+    let output = fixture.sync(&[]);
+    assert_eq!(stderr(&output), "");
+
+    let course = &fixture.course;
+    assert_eq!(
+        read(course, "exercises/intro1.rs"),
+        "fn main() { println!(\"done\") }\n"
+    );
+    assert_eq!(
+        read(course, "exercises/intro2.rs"),
+        "fn main() { todo!(\"fixed\") }\n"
+    );
+    assert_eq!(read(course, "exercises/intro3.rs"), "fn main() { todo!() }\n");
+    // 合并提交的第二个父提交是模板
+    assert_eq!(
+        git(course, &["rev-parse", "HEAD^2"]),
+        fixture.upstream_head()
+    );
+    fixture.assert_clean();
+}
+
+#[test]
+fn rebase_puts_learner_commits_on_top_of_the_template() {
+    let fixture = Fixture::new();
+    fixture.publish(typical_update);
+
+    let output = fixture.sync(&["--rebase"]);
+    assert_eq!(stderr(&output), "");
+
+    let course = &fixture.course;
+    assert_eq!(
+        read(course, "exercises/intro1.rs"),
+        "fn main() { println!(\"done\") }\n"
+    );
+    assert_eq!(
+        read(course, "exercises/intro2.rs"),
+        "fn main() { todo!(\"fixed\") }\n"
+    );
+    assert!(course.join("exercises/intro3.rs").exists());
+    assert_eq!(git(course, &["rev-list", "--merges", "HEAD"]), "");
+    let upstream = fixture.upstream_head();
+    git(course, &["merge-base", "--is-ancestor", &upstream, "HEAD"]);
+    fixture.assert_clean();
+}
+
+#[test]
+fn merge_keeps_learner_file_that_the_template_deleted() {
+    let fixture = Fixture::new();
+    fixture.publish(|template| fs::remove_file(template.join("exercises/intro1.rs")).unwrap());
+
+    let output = fixture.sync(&[]);
+    assert_eq!(stderr(&output), "");
+    assert_eq!(
+        read(&fixture.course, "exercises/intro1.rs"),
+        "fn main() { println!(\"done\") }\n"
+    );
+    fixture.assert_clean();
+}
+
+#[test]
+fn rebase_aborts_on_conflicts_it_cannot_resolve() {
+    let fixture = Fixture::new();
+    fixture.publish(|template| fs::remove_file(template.join("exercises/intro1.rs")).unwrap());
+    let before = fixture.head();
+
+    let output = fixture.sync(&["--rebase"]);
+    let stderr = stderr(&output);
+    assert!(stderr.contains("同步课程失败"), "{}", stderr);
+    assert!(stderr.contains("已撤销本次变基"), "{}", stderr);
+    assert_eq!(fixture.head(), before);
+    fixture.assert_clean();
+}
+
+#[test]
+fn merge_that_fails_without_conflicts_is_aborted_with_git_error() {
+    let fixture = Fixture::new();
+    fixture.publish(typical_update);
+    // 模板提交没有签名，git merge 在产生任何冲突之前就会失败
+    git(&fixture.course, &["config", "merge.verifySignatures", "true"]);
+    let before = fixture.head();
+
+    let output = fixture.sync(&[]);
+    let stderr = stderr(&output);
+    assert!(stderr.contains("已撤销本次合并"), "{}", stderr);
+    assert!(stderr.contains("GPG signature"), "{}", stderr);
+    assert_eq!(fixture.head(), before);
+    fixture.assert_clean();
+}
+
+#[test]
+fn already_up_to_date_does_nothing() {
+    let fixture = Fixture::new();
+    let before = fixture.head();
+
+    let output = fixture.sync(&[]);
+    assert_eq!(stderr(&output), "");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("已包含模板的所有更新"));
+    assert_eq!(fixture.head(), before);
+}